@@ -0,0 +1,60 @@
+//! Decoding inbound messages, the hot path of a poller or trap listener.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use snmp::{
+    oid, Message, ObjectIdentifier, ObjectIdentifierExt, Pdu, PduTag, TimeTicks, Value, VarBinding,
+    Version,
+};
+
+/// A response to a poll of 20 interface counters, with a description each.
+fn response() -> Vec<u8> {
+    let mut bindings = vec![];
+    for i in 1..=20 {
+        bindings.push(VarBinding::new(
+            oid! {1,3,6,1,2,1,2,2,1,2}.child(&[i]),
+            Value::from(format!("GigabitEthernet0/{}", i).as_str()),
+        ));
+        bindings.push(VarBinding::new(
+            oid! {1,3,6,1,2,1,2,2,1,10}.child(&[i]),
+            Value::Counter32(i * 1_000_003),
+        ));
+    }
+    let pdu = Pdu::new(PduTag::GetResponse, 1).with_bindings(&bindings);
+    Message::new(Version::V2C, "public", pdu)
+        .to_bytes()
+        .unwrap()
+}
+
+/// A linkDown notification.
+fn trap() -> Vec<u8> {
+    let bindings = [
+        VarBinding::new(
+            oid! {1,3,6,1,2,1,1,3,0},
+            Value::Timeticks(TimeTicks::new(123_456)),
+        ),
+        VarBinding::new(
+            oid! {1,3,6,1,6,3,1,1,4,1,0},
+            Value::Oid(oid! {1,3,6,1,6,3,1,1,5,3}),
+        ),
+        VarBinding::new(oid! {1,3,6,1,2,1,2,2,1,1,7}, Value::Integer(7)),
+    ];
+    let pdu = Pdu::new(PduTag::TrapV2, 1).with_bindings(&bindings);
+    Message::new(Version::V2C, "public", pdu)
+        .to_bytes()
+        .unwrap()
+}
+
+fn decode(c: &mut Criterion) {
+    let response = response();
+    c.bench_function("decode response", |b| {
+        b.iter(|| Message::from_bytes(black_box(&response)).unwrap())
+    });
+
+    let trap = trap();
+    c.bench_function("decode trap", |b| {
+        b.iter(|| Message::from_bytes(black_box(&trap)).unwrap())
+    });
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
@@ -0,0 +1,84 @@
+//! Repeated walks of a large table, as a poller does every interval.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use snmp::{
+    oid, Client, Message, ObjectIdentifier, ObjectIdentifierExt, Transport, Value, VarBinding,
+    Version,
+};
+
+use std::collections::VecDeque;
+use std::io;
+
+const ROWS: u32 = 1000;
+
+/// An agent in the same thread answering GetNext requests from a sorted MIB,
+/// so the benchmark measures the client rather than the network.
+struct TableAgent {
+    mib: Vec<VarBinding>,
+    replies: VecDeque<Vec<u8>>,
+}
+
+impl TableAgent {
+    /// An `ifTable` of `ROWS` interfaces.
+    fn new() -> TableAgent {
+        let entry = oid! {1,3,6,1,2,1,2,2,1};
+        let mut mib = vec![];
+        for column in 1..=8 {
+            for index in 1..=ROWS {
+                let value = match column {
+                    2 => Value::from(format!("eth{}", index).as_str()),
+                    5 => Value::Gauge32(1_000_000_000),
+                    _ => Value::Integer(index as i32),
+                };
+                mib.push(VarBinding::new(entry.child(&[column, index]), value));
+            }
+        }
+
+        TableAgent {
+            mib,
+            replies: VecDeque::new(),
+        }
+    }
+
+    fn next(&self, name: &ObjectIdentifier) -> VarBinding {
+        let i = self
+            .mib
+            .partition_point(|vb| vb.name.as_slice() <= name.as_slice());
+        match self.mib.get(i) {
+            Some(vb) => vb.clone(),
+            None => VarBinding::new(name.clone(), Value::EndOfMIBView),
+        }
+    }
+}
+
+impl Transport for TableAgent {
+    fn send(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let request = Message::from_bytes(buf).unwrap();
+        let bindings: Vec<_> = request
+            .data()
+            .bindings()
+            .iter()
+            .map(|vb| self.next(&vb.name))
+            .collect();
+        let response = request.make_response(&bindings);
+        self.replies.push_back(response.to_bytes().unwrap());
+        Ok(buf.len())
+    }
+
+    fn recv(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let reply = self.replies.pop_front().ok_or(io::ErrorKind::WouldBlock)?;
+        buf[..reply.len()].copy_from_slice(&reply);
+        Ok(reply.len())
+    }
+}
+
+fn walk(c: &mut Criterion) {
+    let mut agent = TableAgent::new();
+    let mut client = Client::new(Version::V2C, &mut agent);
+    c.bench_function("interfaces of a 1000-row ifTable", |b| {
+        b.iter(|| assert_eq!(client.interfaces().unwrap().len(), ROWS as usize))
+    });
+}
+
+criterion_group!(benches, walk);
+criterion_main!(benches);
@@ -0,0 +1,178 @@
+//! Configuring a client and opening its socket in one place.
+
+use crate::client::{Client, RetryPolicy};
+use crate::error::SnmpError;
+use crate::types::Version;
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+
+pub struct ClientBuilder<'a> {
+    version: Version,
+    read_community: &'a str,
+    write_community: &'a str,
+    retry_policy: Option<RetryPolicy>,
+    retries: u32,
+    source: Option<IpAddr>,
+    source_port: u16,
+}
+
+impl<'a> ClientBuilder<'a> {
+    pub fn new(version: Version) -> ClientBuilder<'a> {
+        ClientBuilder {
+            version,
+            read_community: "public",
+            write_community: "private",
+            retry_policy: None,
+            retries: 0,
+            source: None,
+            source_port: 0,
+        }
+    }
+
+    pub fn communities(mut self, read_community: &'a str, write_community: &'a str) -> Self {
+        self.read_community = read_community;
+        self.write_community = write_community;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.retry_policy = timeout.map(RetryPolicy::Fixed);
+        self
+    }
+
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Binds the socket to `addr` instead of letting the OS pick the source
+    /// address. On a multi-homed host this decides which interface requests
+    /// leave from, and so which address the agent sees and replies to.
+    pub fn source_address(mut self, addr: IpAddr) -> Self {
+        self.source = Some(addr);
+        self
+    }
+
+    /// Binds the socket to `port` instead of an ephemeral one, for firewalls
+    /// that only let SNMP through from a fixed source port. Only one socket
+    /// can hold the port at a time.
+    pub fn source_port(mut self, port: u16) -> Self {
+        self.source_port = port;
+        self
+    }
+
+    /// Opens a UDP socket connected to `agent`, bound to the source address
+    /// and port if they were set.
+    pub fn connect<A: ToSocketAddrs>(&self, agent: A) -> Result<UdpSocket, SnmpError> {
+        let agent = agent
+            .to_socket_addrs()
+            .map_err(SnmpError::Connect)?
+            .next()
+            .ok_or_else(|| {
+                SnmpError::Connect(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "agent address resolved to nothing",
+                ))
+            })?;
+        let source = self.source.unwrap_or(match agent {
+            SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        });
+
+        let socket = UdpSocket::bind((source, self.source_port)).map_err(|e| {
+            if e.kind() == io::ErrorKind::AddrInUse {
+                SnmpError::Connect(io::Error::new(
+                    e.kind(),
+                    format!("source port {} is already in use", self.source_port),
+                ))
+            } else {
+                SnmpError::Connect(e)
+            }
+        })?;
+        socket.connect(agent).map_err(SnmpError::Connect)?;
+        Ok(socket)
+    }
+
+    /// Creates a client using `socket`, which is usually one returned by `connect`.
+    pub fn build(&self, socket: &'a mut UdpSocket) -> Client<'a> {
+        let mut client = Client::new(self.version, socket);
+        client.set_communities(self.read_community, self.write_community);
+        if let Some(policy) = self.retry_policy {
+            client.set_retry_policy(policy);
+        }
+        client.set_retries(self.retries);
+        client
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oid;
+    use crate::testing::*;
+    use crate::types::{ObjectIdentifier, Value, VarBinding};
+
+    #[test]
+    fn connect_binds_source_address() {
+        let agent = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let source: IpAddr = "127.0.0.2".parse().unwrap();
+
+        let socket = ClientBuilder::new(Version::V2C)
+            .source_address(source)
+            .connect(agent.local_addr().unwrap())
+            .unwrap();
+        assert_eq!(socket.local_addr().unwrap().ip(), source);
+        assert_eq!(socket.peer_addr().unwrap(), agent.local_addr().unwrap());
+    }
+
+    #[test]
+    fn connect_binds_source_port() {
+        let agent = UdpSocket::bind("127.0.0.1:0").unwrap();
+        // Find a free port by letting the OS pick one and releasing it.
+        let port = UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let builder = ClientBuilder::new(Version::V2C)
+            .source_address("127.0.0.1".parse().unwrap())
+            .source_port(port);
+        let socket = builder.connect(agent.local_addr().unwrap()).unwrap();
+        assert_eq!(socket.local_addr().unwrap().port(), port);
+
+        match builder.connect(agent.local_addr().unwrap()) {
+            Err(SnmpError::Connect(e)) => {
+                assert_eq!(e.kind(), io::ErrorKind::AddrInUse);
+                assert_eq!(
+                    e.to_string(),
+                    format!("source port {} is already in use", port)
+                );
+            }
+            r => panic!("Unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn build_applies_settings() {
+        let mut socket = agent(get_agent(vec![VarBinding::new(
+            oid! {1,3,6,1,2,1,1,5,0},
+            Value::Integer(5),
+        )]));
+        let mut c = ClientBuilder::new(Version::V1)
+            .communities("reader", "writer")
+            .timeout(Some(Duration::from_secs(1)))
+            .build(&mut socket);
+
+        assert_eq!(c.version(), Version::V1);
+        let vars = c.get(&[oid! {1,3,6,1,2,1,1,5,0}]).unwrap();
+        assert_eq!(vars[0].value, Value::Integer(5));
+    }
+}
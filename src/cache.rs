@@ -0,0 +1,272 @@
+//! Reusing recent GET responses instead of asking the agent again.
+
+use crate::client::Client;
+use crate::transport::Transport;
+use crate::types::{ObjectIdentifier, VarBinding, Version};
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+/// What a cached response answers: the OIDs requested, and everything else
+/// about the request that can change what the agent returns for them.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct CacheKey {
+    pub(crate) version: Version,
+    pub(crate) community: Option<String>,
+    pub(crate) security_name: String,
+    pub(crate) context_engine_id: Option<Vec<u8>>,
+    pub(crate) context_name: String,
+    /// The agent of an unconnected client, which can be pointed elsewhere
+    /// at any time.
+    pub(crate) agent: Option<SocketAddr>,
+    pub(crate) oids: Vec<ObjectIdentifier>,
+}
+
+struct Entry {
+    stored_at: Instant,
+    last_used: u64,
+    bindings: Vec<VarBinding>,
+}
+
+/// The bindings of recent GETs, keyed by the request they answer.
+pub(crate) struct ResponseCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: HashMap<CacheKey, Entry>,
+    uses: u64,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(ttl: Duration, max_entries: usize) -> ResponseCache {
+        ResponseCache {
+            ttl,
+            max_entries,
+            entries: HashMap::new(),
+            uses: 0,
+        }
+    }
+
+    /// An empty cache with the same TTL and size.
+    pub(crate) fn emptied(&self) -> ResponseCache {
+        ResponseCache::new(self.ttl, self.max_entries)
+    }
+
+    /// The bindings stored for `key`, if they are younger than the TTL.
+    pub(crate) fn get(&mut self, key: &CacheKey, now: Instant) -> Option<Vec<VarBinding>> {
+        let entry = self.entries.get_mut(key)?;
+        if now.duration_since(entry.stored_at) >= self.ttl {
+            self.entries.remove(key);
+            return None;
+        }
+
+        self.uses += 1;
+        entry.last_used = self.uses;
+        Some(entry.bindings.clone())
+    }
+
+    /// Stores `bindings` for `key`, evicting the least recently used entry
+    /// if the cache is full.
+    pub(crate) fn insert(&mut self, key: CacheKey, bindings: &[VarBinding], now: Instant) {
+        if self.max_entries == 0 {
+            return;
+        }
+
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.max_entries {
+            let ttl = self.ttl;
+            self.entries
+                .retain(|_, entry| now.duration_since(entry.stored_at) < ttl);
+        }
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.max_entries {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        self.uses += 1;
+        let entry = Entry {
+            stored_at: now,
+            last_used: self.uses,
+            bindings: bindings.to_vec(),
+        };
+        self.entries.insert(key, entry);
+    }
+
+    /// Drops every response holding one of the objects `bindings` SET, from
+    /// any agent or context, as they may no longer be current.
+    pub(crate) fn invalidate(&mut self, bindings: &[VarBinding]) {
+        self.entries
+            .retain(|key, _| !bindings.iter().any(|vb| key.oids.contains(&vb.name)));
+    }
+}
+
+impl<'a, T: Transport> Client<'a, T> {
+    /// Answers a `get` from the response to an earlier one for `ttl`, without
+    /// sending a request, if it asked for the same OIDs of the same agent with
+    /// the same version, community or user, and context. At most
+    /// `max_entries` responses are kept; the least recently used one makes
+    /// way for a new one.
+    ///
+    /// Only `get` is cached, and errors never are. A `set` drops the cached
+    /// responses holding any of the objects it SETs. `ping` and the other
+    /// probes always ask the agent.
+    pub fn enable_cache(&mut self, ttl: Duration, max_entries: usize) {
+        self.set_cache(Some(ResponseCache::new(ttl, max_entries)));
+    }
+
+    pub fn disable_cache(&mut self) {
+        self.set_cache(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::ReplySource;
+    use crate::clock::MockClock;
+    use crate::oid;
+    use crate::pdu::PduTag;
+    use crate::testing::*;
+    use crate::types::{ObjectIdentifierExt, Value};
+
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn binding(i: u32) -> VarBinding {
+        VarBinding::new(oid! {1,3,6,1,4,1,99}.child(&[i]), Value::Integer(i as i32))
+    }
+
+    #[test]
+    fn entries_expire_and_evict_least_recently_used() {
+        let start = Instant::now();
+        let mut cache = ResponseCache::new(Duration::from_secs(10), 2);
+        let key = |i: u32| CacheKey {
+            version: Version::V2C,
+            community: Some("public".to_owned()),
+            security_name: String::new(),
+            context_engine_id: None,
+            context_name: String::new(),
+            agent: None,
+            oids: vec![binding(i).name],
+        };
+
+        cache.insert(key(1), &[binding(1)], start);
+        cache.insert(key(2), &[binding(2)], start);
+        assert!(cache.get(&key(1), start).is_some());
+        cache.insert(key(3), &[binding(3)], start);
+        assert!(cache.get(&key(2), start).is_none());
+        assert!(cache.get(&key(1), start).is_some());
+        assert!(cache.get(&key(3), start).is_some());
+
+        let later = start + Duration::from_secs(10);
+        assert!(cache.get(&key(1), later).is_none());
+        assert!(cache.get(&key(3), later - Duration::from_secs(1)).is_some());
+    }
+
+    /// An agent answering GETs from `mib` and acknowledging SETs, which
+    /// counts the requests it receives in `requests`.
+    fn counting_agent(mib: Vec<VarBinding>, requests: Rc<Cell<usize>>) -> MockTransport {
+        let mut lookup = get_agent(mib);
+        MockTransport::agent(move |request| {
+            requests.set(requests.get() + 1);
+            match request.data().tag() {
+                PduTag::SetRequest => vec![response(request, request.data().bindings())],
+                _ => vec![lookup(request)],
+            }
+        })
+    }
+
+    #[test]
+    fn repeated_get_is_answered_from_cache() {
+        let requests = Rc::new(Cell::new(0));
+        let mut transport = counting_agent(vec![binding(1), binding(2)], requests.clone());
+        let clock = MockClock::new();
+        let mut c = Client::new(Version::V2C, &mut transport);
+        c.set_clock(clock.clone());
+        c.enable_cache(Duration::from_secs(5), 16);
+
+        let oids = [binding(1).name, binding(2).name];
+        let first = c.get(&oids).unwrap();
+        assert_eq!(requests.replace(0), 1);
+
+        clock.advance(Duration::from_secs(4));
+        let second = c.get(&oids).unwrap();
+        assert_eq!(requests.replace(0), 0);
+        assert_eq!(
+            first.iter().map(|vb| vb.to_pair()).collect::<Vec<_>>(),
+            second.iter().map(|vb| vb.to_pair()).collect::<Vec<_>>()
+        );
+
+        c.get(&oids[..1]).unwrap();
+        assert_eq!(requests.replace(0), 1);
+
+        clock.advance(Duration::from_secs(1));
+        c.get(&oids).unwrap();
+        assert_eq!(requests.replace(0), 1);
+    }
+
+    #[test]
+    fn cache_is_per_community_and_agent() {
+        let requests = Rc::new(Cell::new(0));
+        let mut transport = counting_agent(vec![binding(1)], requests.clone());
+        let mut c = Client::new(Version::V2C, &mut transport);
+        c.set_clock(MockClock::new());
+        c.enable_cache(Duration::from_secs(5), 16);
+        let oids = [binding(1).name];
+
+        c.get(&oids).unwrap();
+        c.set_communities("private", "private");
+        c.get(&oids).unwrap();
+        c.set_version(Version::V1);
+        c.get(&oids).unwrap();
+        assert_eq!(requests.replace(0), 3);
+
+        c.set_unconnected_agent("127.0.0.1:161".parse().unwrap(), ReplySource::Any);
+        c.get(&oids).unwrap();
+        c.set_unconnected_agent("127.0.0.2:161".parse().unwrap(), ReplySource::Any);
+        c.get(&oids).unwrap();
+        assert_eq!(requests.replace(0), 2);
+
+        c.get(&oids).unwrap();
+        assert_eq!(requests.replace(0), 0);
+    }
+
+    #[test]
+    fn set_drops_cached_responses() {
+        let requests = Rc::new(Cell::new(0));
+        let mut transport = counting_agent(vec![binding(1), binding(2)], requests.clone());
+        let mut c = Client::new(Version::V2C, &mut transport);
+        c.set_clock(MockClock::new());
+        c.enable_cache(Duration::from_secs(5), 16);
+
+        c.get(&[binding(1).name, binding(2).name]).unwrap();
+        c.get(&[binding(2).name]).unwrap();
+        c.set(&[binding(1)]).unwrap();
+        assert_eq!(requests.replace(0), 3);
+
+        c.get(&[binding(2).name]).unwrap();
+        assert_eq!(requests.replace(0), 0);
+        c.get(&[binding(1).name, binding(2).name]).unwrap();
+        assert_eq!(requests.replace(0), 1);
+    }
+
+    #[test]
+    fn ping_bypasses_cache() {
+        let uptime = VarBinding::new(oid! {1,3,6,1,2,1,1,3,0}, Value::Integer(1));
+        let requests = Rc::new(Cell::new(0));
+        let mut transport = counting_agent(vec![uptime.clone()], requests.clone());
+        let mut c = Client::new(Version::V2C, &mut transport);
+        c.set_clock(MockClock::new());
+        c.enable_cache(Duration::from_secs(5), 16);
+
+        c.get(&[uptime.name]).unwrap();
+        assert!(c.ping().unwrap());
+        assert!(c.ping().unwrap());
+        assert_eq!(requests.get(), 3);
+    }
+}
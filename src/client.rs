@@ -1,44 +1,507 @@
-use crate::pdu::{Message, Pdu, PduTag};
-use crate::types::{ObjectIdentifier, Value, VarBinding, Version};
+use crate::cache::{CacheKey, ResponseCache};
+use crate::clock::{Clock, SystemClock};
+use crate::error::{ErrorStatus, SnmpError};
+use crate::pdu::{declared_size, DecodeLimits, Message, Pdu, PduTag};
+use crate::transport::Transport;
+use crate::types::{ObjectIdentifier, ObjectIdentifierDisplayWrapper, Value, VarBinding, Version};
+use crate::v3::EngineInfo;
+use crate::walk::WalkStrictness;
 
-use rand;
-use rasn::ber::{decode, encode};
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use std::net::UdpSocket;
-use std::{collections::HashMap, future::Ready};
+/// How long the rest of a split response is waited for when the client has
+/// no timeout of its own.
+const REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(1);
 
-pub struct Client<'a> {
+/// How long to wait for a response before retransmitting a request.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RetryPolicy {
+    /// Waits the same time for every attempt.
+    Fixed(Duration),
+    /// Waits `base` for the first attempt and `factor` times longer for each
+    /// retransmission, up to `max`.
+    Exponential {
+        base: Duration,
+        factor: u32,
+        max: Duration,
+    },
+}
+
+impl RetryPolicy {
+    /// The wait for `attempt`, counting the first transmission as 0.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        match *self {
+            RetryPolicy::Fixed(wait) => wait,
+            RetryPolicy::Exponential { base, factor, max } => base
+                .checked_mul(factor.saturating_pow(attempt))
+                .map_or(max, |wait| wait.min(max)),
+        }
+    }
+}
+
+/// Which replies an unconnected client accepts; see
+/// `Client::set_unconnected_agent`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReplySource {
+    /// Replies from the agent's IP address, from any port.
+    AgentIp,
+    /// Replies from any address at all.
+    Any,
+}
+
+/// A function applied to every value a response carries; see
+/// `Client::set_value_transform`.
+pub type ValueTransform = Box<dyn Fn(&ObjectIdentifier, Value) -> Value + Send + Sync>;
+
+/// A `ValueTransform` as the client keeps it, shared with its clones.
+type SharedTransform = Arc<dyn Fn(&ObjectIdentifier, Value) -> Value + Send + Sync>;
+
+/// What the last response said about its sender, for checking that the agent
+/// answered in the version and community it was asked in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResponseMeta {
+    pub version: Version,
+    /// The community of a community-based response; `None` for SNMPv3.
+    pub community: Option<Vec<u8>>,
+}
+
+impl ResponseMeta {
+    fn of(msg: &Message) -> ResponseMeta {
+        ResponseMeta {
+            version: msg.version(),
+            community: Some(msg.community_bytes().to_vec()),
+        }
+    }
+}
+
+/// Spaces sends at least `interval` apart: a token bucket holding one token.
+#[derive(Clone)]
+struct Throttle {
+    interval: Duration,
+    next_send: Option<Instant>,
+}
+
+/// A manager talking to an agent over `socket`.
+///
+/// Requests go through `&mut self` and wait for their response, so a client
+/// serves one thread at a time. It is `Send` when its transport is. To poll
+/// from several threads at once, give each thread a client of its own with
+/// `clone_with`, on a socket from `try_clone_socket`.
+pub struct Client<'a, T: Transport = UdpSocket> {
     version: Version,
     current_request: i32,
-    read_community: &'a str,
-    write_community: &'a str,
-    socket: &'a mut UdpSocket,
+    read_community: Option<String>,
+    write_community: Option<String>,
+    retry_policy: Option<RetryPolicy>,
+    retries: u32,
+    /// Request-id sent by `send_request` and not yet answered.
+    pending: Option<i32>,
+    decode_limits: DecodeLimits,
+    strict: bool,
+    lenient_errors: bool,
+    context_engine_id: Option<Vec<u8>>,
+    context_name: String,
+    security_name: String,
+    engine: Option<EngineInfo>,
+    clock: Arc<dyn Clock>,
+    /// The agent and accepted replies when the socket is not connected.
+    unconnected: Option<(SocketAddr, ReplySource)>,
+    value_transform: Option<SharedTransform>,
+    cache: Option<ResponseCache>,
+    last_response_at: Option<Instant>,
+    last_latency: Option<Duration>,
+    last_sent_at: Option<Instant>,
+    last_response_meta: Option<ResponseMeta>,
+    consecutive_timeouts: u32,
+    throttle: Option<Throttle>,
+    walk_strictness: WalkStrictness,
+    walk_budget: Option<Duration>,
+    socket: &'a mut T,
 }
 
-impl<'a> Client<'a> {
-    pub fn new(version: Version, socket: &'a mut UdpSocket) -> Client<'a> {
+impl<'a, T: Transport> Client<'a, T> {
+    pub fn new(version: Version, socket: &'a mut T) -> Client<'a, T> {
         Client {
             version,
             current_request: rand::random::<i32>(),
-            read_community: "public",
-            write_community: "private",
+            read_community: Some("public".to_owned()),
+            write_community: Some("private".to_owned()),
+            retry_policy: None,
+            retries: 0,
+            pending: None,
+            decode_limits: DecodeLimits::default(),
+            strict: false,
+            lenient_errors: false,
+            context_engine_id: None,
+            context_name: String::new(),
+            security_name: String::new(),
+            engine: None,
+            clock: Arc::new(SystemClock),
+            unconnected: None,
+            value_transform: None,
+            cache: None,
+            last_response_at: None,
+            last_latency: None,
+            last_sent_at: None,
+            last_response_meta: None,
+            consecutive_timeouts: 0,
+            throttle: None,
+            walk_strictness: WalkStrictness::Strict,
+            walk_budget: None,
+            socket,
+        }
+    }
+
+    /// A client with this one's settings, clock, value transform and SNMPv3
+    /// engine on `socket`, e.g. for another thread. It starts its own random
+    /// request-id sequence with nothing pending, an empty response cache and
+    /// no record of past responses.
+    pub fn clone_with<'b, U: Transport>(&self, socket: &'b mut U) -> Client<'b, U> {
+        Client {
+            version: self.version,
+            current_request: rand::random::<i32>(),
+            read_community: self.read_community.clone(),
+            write_community: self.write_community.clone(),
+            retry_policy: self.retry_policy,
+            retries: self.retries,
+            pending: None,
+            decode_limits: self.decode_limits,
+            strict: self.strict,
+            lenient_errors: self.lenient_errors,
+            context_engine_id: self.context_engine_id.clone(),
+            context_name: self.context_name.clone(),
+            security_name: self.security_name.clone(),
+            engine: self.engine.clone(),
+            clock: self.clock.clone(),
+            unconnected: self.unconnected,
+            value_transform: self.value_transform.clone(),
+            cache: self.cache.as_ref().map(ResponseCache::emptied),
+            last_response_at: None,
+            last_latency: None,
+            last_sent_at: None,
+            last_response_meta: None,
+            consecutive_timeouts: 0,
+            throttle: self.throttle.as_ref().map(|throttle| Throttle {
+                interval: throttle.interval,
+                next_send: None,
+            }),
+            walk_strictness: self.walk_strictness,
+            walk_budget: self.walk_budget,
             socket,
         }
     }
 
-    pub fn set_communities(&mut self, read_community: &'a str, write_community: &'a str) {
-        self.read_community = read_community;
-        self.write_community = write_community;
+    /// Locks the client to SNMPv3. The community strings are discarded and any
+    /// request made while `version` is not `V3` fails instead of sending a
+    /// community-based message.
+    pub fn v3_only(mut self) -> Self {
+        self.read_community = None;
+        self.write_community = None;
+        self
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Switches the version later requests are sent with. A client locked
+    /// with `v3_only` still refuses the community-based versions.
+    pub fn set_version(&mut self, version: Version) {
+        self.version = version;
+    }
+
+    /// Replaces the clock timeouts and the SNMPv3 engine time are measured
+    /// with, which is the system clock by default.
+    pub fn set_clock<C: Clock + 'static>(&mut self, clock: C) {
+        self.clock = Arc::new(clock);
+    }
+
+    pub(crate) fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
+    /// Sends requests to `agent` with `send_to` and accepts replies as
+    /// `accept` allows, for a socket that is not connected. Some agents, and
+    /// NATs in front of them, reply from another port than the one the
+    /// request went to, which a connected socket silently drops.
+    ///
+    /// Accepting replies from other ports lets anyone who can reach the
+    /// client's port from the agent's host inject responses; `Any` extends
+    /// that to every host. Request-ids still have to match.
+    pub fn set_unconnected_agent(&mut self, agent: SocketAddr, accept: ReplySource) {
+        self.unconnected = Some((agent, accept));
+    }
+
+    /// Passes every value a response carries through `transform` before it
+    /// is returned, e.g. to scale a sensor reading or redact a string.
+    pub fn set_value_transform(&mut self, transform: ValueTransform) {
+        self.value_transform = Some(Arc::from(transform));
+    }
+
+    /// When the last response from the agent arrived, by the client's clock.
+    /// Error responses count: they show the agent is up.
+    pub fn last_response_at(&self) -> Option<Instant> {
+        self.last_response_at
+    }
+
+    /// The round-trip time of the last response: how long after the datagram
+    /// last sent before it the response arrived, by the client's clock.
+    pub fn last_latency(&self) -> Option<Duration> {
+        self.last_latency
+    }
+
+    /// The version and community of the last response to a request. The
+    /// client accepts any response with the right request-id, so a v2c reply
+    /// to a v1 request or one in another community only shows here.
+    pub fn last_response_meta(&self) -> Option<&ResponseMeta> {
+        self.last_response_meta.as_ref()
+    }
+
+    /// How many attempts in a row, retransmissions included, have timed out
+    /// since the last response.
+    pub fn consecutive_timeouts(&self) -> u32 {
+        self.consecutive_timeouts
+    }
+
+    /// Sends at most `max_per_second` datagrams a second, sleeping on the
+    /// client's clock before a send that would come too soon after the last.
+    /// Retransmissions count, so a timing-out agent is not hammered either.
+    /// `None`, the default, sends as fast as requests are made.
+    ///
+    /// Fails with `InvalidRateLimit` if `max_per_second` is not positive, or
+    /// so small the wait between sends cannot be represented.
+    pub fn set_rate_limit(&mut self, max_per_second: Option<f64>) -> Result<(), SnmpError> {
+        let rate = match max_per_second {
+            Some(rate) => rate,
+            None => {
+                self.throttle = None;
+                return Ok(());
+            }
+        };
+
+        let interval = Some(rate)
+            .filter(|rate| *rate > 0.0)
+            .and_then(|rate| Duration::try_from_secs_f64(1.0 / rate).ok())
+            .filter(|interval| self.now().checked_add(*interval).is_some())
+            .ok_or(SnmpError::InvalidRateLimit(rate))?;
+        self.throttle = Some(Throttle {
+            interval,
+            next_send: None,
+        });
+        Ok(())
+    }
+
+    /// Sets how walks treat an agent returning an OID that does not follow
+    /// the last one. `Strict`, the default, fails the walk.
+    pub fn set_walk_strictness(&mut self, strictness: WalkStrictness) {
+        self.walk_strictness = strictness;
+    }
+
+    pub fn walk_strictness(&self) -> WalkStrictness {
+        self.walk_strictness
+    }
+
+    /// Limits how long a whole walk may take, on top of the timeout of each of
+    /// its requests. A walk that has not finished in time stops before its
+    /// next request and returns what it collected, so a slow agent cannot
+    /// stretch a long walk indefinitely. `None`, the default, leaves walks
+    /// unbounded.
+    pub fn set_walk_budget(&mut self, budget: Option<Duration>) {
+        self.walk_budget = budget;
+    }
+
+    pub fn walk_budget(&self) -> Option<Duration> {
+        self.walk_budget
+    }
+
+    /// Sets the request-id of the next request, which is random by default.
+    /// Later requests count up from it, wrapping at `i32::MAX`.
+    pub fn set_request_id(&mut self, next: i32) {
+        self.current_request = next;
+    }
+
+    /// Sets how long to wait for each response. `None` waits forever.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.retry_policy = timeout.map(RetryPolicy::Fixed);
+    }
+
+    pub fn retry_policy(&self) -> Option<RetryPolicy> {
+        self.retry_policy
+    }
+
+    /// Sets how long to wait for the response to each attempt.
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry_policy = Some(policy);
+    }
+
+    /// Sets how many times a request is retransmitted after timing out.
+    pub fn set_retries(&mut self, retries: u32) {
+        self.retries = retries;
+    }
+
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Selects the SNMPv3 context requests are addressed to. `None` uses the
+    /// agent's own engine id; the default context name is empty.
+    pub fn set_context(&mut self, context_engine_id: Option<Vec<u8>>, context_name: &str) {
+        self.context_engine_id = context_engine_id;
+        self.context_name = context_name.to_owned();
+    }
+
+    /// The context engine id and name set by [`Client::set_context`].
+    pub fn context(&self) -> (Option<&[u8]>, &str) {
+        (self.context_engine_id.as_deref(), &self.context_name)
+    }
+
+    /// Sets the SNMPv3 user requests are sent as.
+    pub fn set_security_name(&mut self, security_name: &str) {
+        self.security_name = security_name.to_owned();
+    }
+
+    pub fn security_name(&self) -> &str {
+        &self.security_name
+    }
+
+    /// The agent's SNMPv3 engine, once discovered.
+    pub fn engine(&self) -> Option<&EngineInfo> {
+        self.engine.as_ref()
+    }
+
+    pub(crate) fn set_cache(&mut self, cache: Option<ResponseCache>) {
+        self.cache = cache;
+    }
+
+    pub(crate) fn set_engine(&mut self, engine: EngineInfo) {
+        self.engine = Some(engine);
+    }
+
+    /// Sets the bounds responses must stay within to be decoded.
+    pub fn set_decode_limits(&mut self, limits: DecodeLimits) {
+        self.decode_limits = limits;
+    }
+
+    /// In strict mode, responses that break the rules of the client's version
+    /// are rejected with `SnmpError::Decode`: SNMPv1 agents must report
+    /// missing objects with `noSuchName` rather than the SNMPv2 exception
+    /// values.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Works around agents that leave a stray error-status in successful
+    /// responses. When enabled, a non-zero error-status is ignored if the
+    /// response has a binding for every one requested and none of them is
+    /// `Null` or an exception value. Off by default.
+    ///
+    /// SET responses echo the request whether or not it failed, so their
+    /// error-status is never ignored.
+    pub fn set_lenient_errors(&mut self, lenient: bool) {
+        self.lenient_errors = lenient;
+    }
+
+    /// May be called again at any time, e.g. to reuse the client for another
+    /// agent. Has no effect on a client locked with `v3_only`.
+    pub fn set_communities(&mut self, read_community: &str, write_community: &str) {
+        if self.read_community.is_none() {
+            return;
+        }
+
+        self.read_community = Some(read_community.to_owned());
+        self.write_community = Some(write_community.to_owned());
+    }
+
+    pub(crate) fn read_community(&self) -> Option<&str> {
+        self.read_community.as_deref()
+    }
+
+    /// Replaces the read community alone. Has no effect on a client locked
+    /// with `v3_only`.
+    pub(crate) fn set_read_community(&mut self, community: &str) {
+        if self.read_community.is_some() {
+            self.read_community = Some(community.to_owned());
+        }
+    }
+
+    /// GETs `oids`. Repeated OIDs are requested once, at their first position,
+    /// so the response holds one binding per distinct OID.
+    ///
+    /// If the agent answers `tooBig`, the OIDs are split in halves that are
+    /// requested separately, as often as needed, and the results are joined
+    /// back in the original order.
+    ///
+    /// The `index` of an `SnmpError::Agent`, such as an SNMPv1 `noSuchName`,
+    /// counts in the request actually sent: the distinct OIDs, or the half of
+    /// them after a `tooBig` split. Its `oid` names the object either way.
+    pub fn get(&mut self, oids: &[ObjectIdentifier]) -> Result<Vec<VarBinding>, SnmpError> {
+        let oids = distinct(oids);
+        if self.cache.is_none() {
+            return self.get_distinct(&oids);
+        }
+
+        let key = self.cache_key(oids);
+        let now = self.now();
+        if let Some(vars) = self.cache.as_mut().and_then(|cache| cache.get(&key, now)) {
+            return Ok(vars);
+        }
+
+        let vars = self.get_distinct(&key.oids)?;
+        let now = self.now();
+        if let Some(cache) = &mut self.cache {
+            cache.insert(key, &vars, now);
+        }
+        Ok(vars)
     }
 
-    pub fn get(&mut self, oids: &[ObjectIdentifier]) -> Result<Vec<VarBinding>, i32> {
+    fn cache_key(&self, oids: Vec<ObjectIdentifier>) -> CacheKey {
+        CacheKey {
+            version: self.version,
+            community: self.read_community.clone(),
+            security_name: self.security_name.clone(),
+            context_engine_id: self.context_engine_id.clone(),
+            context_name: self.context_name.clone(),
+            agent: self.unconnected.map(|(agent, _)| agent),
+            oids,
+        }
+    }
+
+    /// GETs `oids`, which must be distinct, bypassing the response cache.
+    pub(crate) fn get_distinct(
+        &mut self,
+        oids: &[ObjectIdentifier],
+    ) -> Result<Vec<VarBinding>, SnmpError> {
         let request_id = self.increment_request();
         let pdu = Pdu::new(PduTag::GetRequest, request_id).with_null_bindings(oids);
 
-        self.send_and_recv(pdu)
+        match self.send_and_recv(pdu) {
+            Err(SnmpError::Agent {
+                status: ErrorStatus::TooBig,
+                ..
+            }) if oids.len() > 1 => {
+                let (first, second) = oids.split_at(oids.len() / 2);
+                let mut vars = self.get_distinct(first)?;
+                vars.extend(self.get_distinct(second)?);
+                Ok(vars)
+            }
+            r => r,
+        }
     }
 
-    pub fn get_next(&mut self, oids: &[ObjectIdentifier]) -> Result<Vec<VarBinding>, i32> {
+    /// GETNEXTs `oids`, requesting repeated OIDs once as `get` does.
+    pub fn get_next(&mut self, oids: &[ObjectIdentifier]) -> Result<Vec<VarBinding>, SnmpError> {
+        self.get_next_each(&distinct(oids))
+    }
+
+    /// Like `get_next`, but sends repeated OIDs as given, so the response
+    /// lines up with `oids`.
+    pub(crate) fn get_next_each(
+        &mut self,
+        oids: &[ObjectIdentifier],
+    ) -> Result<Vec<VarBinding>, SnmpError> {
         let request_id = self.increment_request();
         let pdu = Pdu::new(PduTag::GetNextRequest, request_id).with_null_bindings(oids);
 
@@ -50,7 +513,7 @@ impl<'a> Client<'a> {
         non_repeating_oids: &[ObjectIdentifier],
         repetitions: i32,
         repeating_oids: &[ObjectIdentifier],
-    ) -> Result<Vec<VarBinding>, i32> {
+    ) -> Result<Vec<VarBinding>, SnmpError> {
         let request_id = self.increment_request();
         let pdu = Pdu::new(PduTag::GetBulkRequest, request_id)
             .set_bulk_repetitions(non_repeating_oids.len() as i32, repetitions)
@@ -60,28 +523,1456 @@ impl<'a> Client<'a> {
         self.send_and_recv(pdu)
     }
 
-    pub fn set(&mut self, bindings: &[VarBinding]) -> Result<Vec<VarBinding>, i32> {
+    /// Returns the bindings the agent echoes. Some agents leave them out of a
+    /// successful response, so an empty result is a success as well.
+    pub fn set(&mut self, bindings: &[VarBinding]) -> Result<Vec<VarBinding>, SnmpError> {
+        if let Some(cache) = &mut self.cache {
+            cache.invalidate(bindings);
+        }
+
         let request_id = self.increment_request();
         let pdu = Pdu::new(PduTag::SetRequest, request_id).with_bindings(bindings);
 
         self.send_and_recv(pdu)
     }
 
-    fn send_and_recv(&mut self, pdu: Pdu) -> Result<Vec<VarBinding>, i32> {
-        let msg = Message::new(self.version, self.read_community, pdu);
-        let buf = encode(&msg).map_err(|_| -1)?;
-        self.socket.send(&buf).map_err(|_| -2)?;
+    /// Like `set`, but when the agent answers `tooBig` the bindings are split
+    /// in half and SET in separate requests, recursively.
+    ///
+    /// **This gives up the atomicity of SET**: the agent applies each request
+    /// on its own, so a failure can leave the earlier halves applied and the
+    /// rest not. Only use it for bindings that are independent of each other.
+    pub fn set_non_atomic(
+        &mut self,
+        bindings: &[VarBinding],
+    ) -> Result<Vec<VarBinding>, SnmpError> {
+        match self.set(bindings) {
+            Err(SnmpError::Agent {
+                status: ErrorStatus::TooBig,
+                ..
+            }) if bindings.len() > 1 => {
+                let (first, second) = bindings.split_at(bindings.len() / 2);
+                let mut vars = self.set_non_atomic(first)?;
+                vars.extend(self.set_non_atomic(second)?);
+                Ok(vars)
+            }
+            r => r,
+        }
+    }
+
+    /// SETs a single object and returns the value the agent echoes back, or
+    /// `value` itself if the agent echoes nothing.
+    pub fn set_one(&mut self, oid: &ObjectIdentifier, value: Value) -> Result<Value, SnmpError> {
+        let vars = self.set(&[VarBinding::new(oid.clone(), value.clone())])?;
+        Ok(vars.into_iter().next().map_or(value, |var| var.value))
+    }
+
+    /// Sends a single GetBulk request, without walking on from its results.
+    ///
+    /// Each of `non_repeaters` is answered once with its successor, like
+    /// `get_next`. Each of `repeaters` is followed for up to `max_repetitions`
+    /// successors, so e.g. the first 20 rows of a column come from one
+    /// repeater with `max_repetitions` of 20. The bindings are returned as the
+    /// agent sent them: the non-repeaters first, then the repeaters
+    /// interleaved row by row.
+    pub fn bulk_get(
+        &mut self,
+        non_repeaters: &[ObjectIdentifier],
+        max_repetitions: i32,
+        repeaters: &[ObjectIdentifier],
+    ) -> Result<Vec<VarBinding>, SnmpError> {
+        if self.version == Version::V1 {
+            return Err(SnmpError::Unsupported("GetBulk in SNMPv1"));
+        }
+
+        self.get_bulk(non_repeaters, max_repetitions, repeaters)
+    }
+
+    /// Sends `msg` as-is and returns the decoded response carrying the same
+    /// request-id. Responses to other requests are discarded. The response is
+    /// returned even if it carries an error-status.
+    ///
+    /// Requests are retransmitted when they time out, but not when the agent's
+    /// host reports the port unreachable.
+    pub fn exchange(&mut self, msg: Message) -> Result<Message, SnmpError> {
+        if self.read_community.is_none() {
+            return Err(SnmpError::CommunityDisabled(msg.version()));
+        }
+
+        let request_id = msg.data().request_id();
+        let limits = self.decode_limits;
+        self.exchange_bytes(&msg.to_bytes()?, |buf| {
+            let msg = Message::from_bytes_with_limits(buf, limits)?;
+            Ok(Some(msg).filter(|msg| msg.data().request_id() == request_id))
+        })
+    }
+
+    /// Sends `buf`, retransmitting on timeout, until `accept` returns a
+    /// response for one of the received datagrams. `accept` returns `None` to
+    /// discard a datagram.
+    pub(crate) fn exchange_bytes<R, F>(&mut self, buf: &[u8], mut accept: F) -> Result<R, SnmpError>
+    where
+        F: FnMut(&[u8]) -> Result<Option<R>, SnmpError>,
+    {
+        let mut attempts = 0;
+        loop {
+            self.send_datagram(buf)?;
+            let timeout = self.retry_policy.map(|policy| policy.delay(attempts));
+            match self.recv(timeout, &mut accept) {
+                Ok(response) => {
+                    self.record_response();
+                    return Ok(response);
+                }
+                Err(SnmpError::Timeout) => {
+                    self.consecutive_timeouts += 1;
+                    if attempts == self.retries {
+                        return Err(SnmpError::Timeout);
+                    }
+                    attempts += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn recv<R, F>(&mut self, timeout: Option<Duration>, accept: &mut F) -> Result<R, SnmpError>
+    where
+        F: FnMut(&[u8]) -> Result<Option<R>, SnmpError>,
+    {
+        // Datagrams that are not the response must not extend the wait.
+        let deadline = timeout.map(|timeout| self.now() + timeout);
+        let mut rejected = None;
+
+        // A datagram larger than the buffer would be truncated and then look
+        // like the first piece of a split message.
+        let mut recv_buf = vec![0u8; self.decode_limits.max_size];
+        loop {
+            let remaining = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(self.now()) {
+                    Some(remaining) if remaining > Duration::from_secs(0) => Some(remaining),
+                    _ => return Err(timeout_error(rejected)),
+                },
+                None => None,
+            };
+            self.socket
+                .set_read_timeout(remaining)
+                .map_err(SnmpError::Recv)?;
+
+            let size = match self.recv_datagram(&mut recv_buf) {
+                Ok(Ok(size)) => size,
+                Ok(Err(peer)) => {
+                    rejected = Some(peer);
+                    continue;
+                }
+                Err(SnmpError::Timeout) => return Err(timeout_error(rejected)),
+                Err(e) => return Err(e),
+            };
+            let response = match declared_size(&recv_buf[..size]) {
+                Some(total) if total > size => {
+                    let message = self.recv_remainder(&recv_buf[..size], total, deadline)?;
+                    // Pieces of other messages can join up to the right
+                    // length; only the response itself completes this one.
+                    match accept(&message) {
+                        Ok(Some(response)) => Some(response),
+                        _ => return Err(incomplete_message(total)),
+                    }
+                }
+                _ => accept(&recv_buf[..size])?,
+            };
+
+            if let Some(response) = response {
+                return Ok(response);
+            }
+        }
+    }
+
+    /// Collects the rest of a message that `first` is only the start of, as
+    /// some proxies split large responses across datagrams. UDP may reorder
+    /// or drop the pieces, so this is best-effort: a message that cannot be
+    /// completed within the size limit by `deadline`, or within
+    /// `REASSEMBLY_TIMEOUT` for a client without a timeout, is
+    /// `MessageTooLarge`.
+    fn recv_remainder(
+        &mut self,
+        first: &[u8],
+        total: usize,
+        deadline: Option<Instant>,
+    ) -> Result<Vec<u8>, SnmpError> {
+        let max_size = self.decode_limits.max_size;
+        if total > max_size {
+            return Err(SnmpError::MessageTooLarge(format!(
+                "{} bytes exceeds the limit of {}",
+                total, max_size
+            )));
+        }
+
+        let deadline = deadline.unwrap_or_else(|| self.now() + REASSEMBLY_TIMEOUT);
+        let mut message = first.to_vec();
+        let mut recv_buf = vec![0u8; max_size];
+        while message.len() < total {
+            match deadline.checked_duration_since(self.now()) {
+                Some(remaining) if remaining > Duration::from_secs(0) => self
+                    .socket
+                    .set_read_timeout(Some(remaining))
+                    .map_err(SnmpError::Recv)?,
+                _ => break,
+            }
+            match self.recv_datagram(&mut recv_buf) {
+                Ok(Ok(size)) => message.extend_from_slice(&recv_buf[..size]),
+                Ok(Err(_)) => {}
+                Err(SnmpError::Timeout) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        if message.len() != total {
+            return Err(incomplete_message(total));
+        }
+        Ok(message)
+    }
+
+    /// Sends a request without waiting for the response, for callers driving
+    /// the socket from their own event loop. Collect the response with
+    /// `poll_response`; sending another request abandons this one.
+    ///
+    /// Not available in SNMPv3, whose messages are only built by the requests
+    /// that wait for their response.
+    pub fn send_request(&mut self, tag: PduTag, bindings: &[VarBinding]) -> Result<(), SnmpError> {
+        if self.version == Version::V3 {
+            return Err(SnmpError::Unsupported("send_request in SNMPv3"));
+        }
+        self.pending = Some(self.send_pdu(tag, bindings)?);
+        Ok(())
+    }
+
+    /// Checks for the response to the last `send_request` without blocking.
+    /// Returns `Ok(None)` if it has not arrived yet, or if nothing is pending.
+    ///
+    /// The socket is switched to nonblocking mode for the read and back
+    /// afterwards.
+    pub fn poll_response(&mut self) -> Result<Option<Vec<VarBinding>>, SnmpError> {
+        let request_id = match self.pending {
+            Some(request_id) => request_id,
+            None => return Ok(None),
+        };
+
+        self.socket.set_nonblocking(true).map_err(SnmpError::Recv)?;
+        let r = self.recv_nonblocking(request_id);
+        self.socket
+            .set_nonblocking(false)
+            .map_err(SnmpError::Recv)?;
+
+        let msg = match r? {
+            Some(msg) => msg,
+            None => return Ok(None),
+        };
+        self.pending = None;
+        self.record_response();
+        self.last_response_meta = Some(ResponseMeta::of(&msg));
+        self.check_response(msg.data())?;
+        response_bindings(self.version, msg.data()).map(Some)
+    }
+
+    fn recv_nonblocking(&mut self, request_id: i32) -> Result<Option<Message>, SnmpError> {
+        let mut recv_buf = vec![0u8; self.decode_limits.max_size];
+        loop {
+            let size = match self.recv_datagram(&mut recv_buf) {
+                Ok(Ok(size)) => size,
+                Ok(Err(_)) => continue,
+                Err(SnmpError::Timeout) => return Ok(None),
+                Err(e) => return Err(e),
+            };
+            let msg = Message::from_bytes_with_limits(&recv_buf[..size], self.decode_limits)?;
+
+            if msg.data().request_id() == request_id {
+                return Ok(Some(msg));
+            }
+        }
+    }
+
+    /// Sends an unconfirmed PDU such as a trap, without waiting for anything
+    /// in return. Not available in SNMPv3.
+    pub(crate) fn notify(&mut self, tag: PduTag, bindings: &[VarBinding]) -> Result<(), SnmpError> {
+        if self.version == Version::V3 {
+            return Err(SnmpError::Unsupported("SNMPv3 notifications"));
+        }
+        self.send_pdu(tag, bindings).map(|_| ())
+    }
+
+    /// Sends a PDU once in a community-based message and returns its
+    /// request-id.
+    fn send_pdu(&mut self, tag: PduTag, bindings: &[VarBinding]) -> Result<i32, SnmpError> {
+        let request_id = self.increment_request();
+        let pdu = Pdu::new(tag, request_id).with_bindings(bindings);
+        let buf = Message::new(self.version, self.community(tag)?, pdu).to_bytes()?;
+
+        self.send_datagram(&buf)?;
+        Ok(request_id)
+    }
+
+    fn send_datagram(&mut self, buf: &[u8]) -> Result<(), SnmpError> {
+        self.wait_for_throttle();
+        self.last_sent_at = Some(self.now());
+        match self.unconnected {
+            Some((agent, _)) => self.socket.send_to(buf, agent),
+            None => self.socket.send(buf),
+        }
+        .map_err(send_error)?;
+
+        Ok(())
+    }
+
+    fn wait_for_throttle(&mut self) {
+        let throttle = match &mut self.throttle {
+            Some(throttle) => throttle,
+            None => return,
+        };
+
+        let mut now = self.clock.now();
+        if let Some(wait) = throttle
+            .next_send
+            .and_then(|next| next.checked_duration_since(now))
+        {
+            self.clock.sleep(wait);
+            now = self.clock.now();
+        }
+        throttle.next_send = now.checked_add(throttle.interval);
+    }
+
+    /// Receives a datagram and returns its size, or the address it came from
+    /// if an unconnected client does not accept replies from there.
+    fn recv_datagram(&mut self, buf: &mut [u8]) -> Result<Result<usize, SocketAddr>, SnmpError> {
+        let (agent, accept) = match self.unconnected {
+            Some(unconnected) => unconnected,
+            None => return self.socket.recv(buf).map(Ok).map_err(recv_error),
+        };
+
+        let (size, peer) = self.socket.recv_from(buf).map_err(recv_error)?;
+        match accept {
+            ReplySource::AgentIp if peer.ip() != agent.ip() => Ok(Err(peer)),
+            _ => Ok(Ok(size)),
+        }
+    }
+
+    /// The community for a request: SetRequests use the write community,
+    /// everything else the read community.
+    fn community(&self, tag: PduTag) -> Result<&str, SnmpError> {
+        let community = match tag {
+            PduTag::SetRequest => &self.write_community,
+            _ => &self.read_community,
+        };
+
+        match community.as_deref() {
+            Some(community) => Ok(community),
+            None if self.version != Version::V3 => Err(SnmpError::CommunityDisabled(self.version)),
+            None => Err(SnmpError::Unsupported("SNMPv3 message security")),
+        }
+    }
+
+    fn send_and_recv(&mut self, pdu: Pdu) -> Result<Vec<VarBinding>, SnmpError> {
+        // Bindings a response needs for a stray error-status to be ignored.
+        let lenient = match pdu.tag() {
+            PduTag::SetRequest => None,
+            _ if self.lenient_errors => Some(pdu.bindings().len()),
+            _ => None,
+        };
+
+        let vars = if self.version == Version::V3 {
+            let msg = self.exchange_v3(pdu)?;
+            self.last_response_meta = Some(ResponseMeta {
+                version: Version::V3,
+                community: None,
+            });
+            self.bindings_from(msg.scoped_pdu().data(), lenient)?
+        } else {
+            let request = Message::new(self.version, self.community(pdu.tag())?, pdu);
+            let msg = self.exchange(request)?;
+            self.last_response_meta = Some(ResponseMeta::of(&msg));
+            self.check_response(msg.data())?;
+            self.bindings_from(msg.data(), lenient)?
+        };
+
+        Ok(self.transform_values(vars))
+    }
+
+    fn bindings_from(
+        &self,
+        response: &Pdu,
+        lenient: Option<usize>,
+    ) -> Result<Vec<VarBinding>, SnmpError> {
+        match lenient {
+            Some(requested) if is_complete(response, requested) => Ok(response.bindings().to_vec()),
+            _ => response_bindings(self.version, response),
+        }
+    }
+
+    fn transform_values(&self, vars: Vec<VarBinding>) -> Vec<VarBinding> {
+        let transform = match &self.value_transform {
+            Some(transform) => transform,
+            None => return vars,
+        };
+
+        vars.into_iter()
+            .map(|var| {
+                let value = transform(&var.name, var.value);
+                VarBinding::new(var.name, value)
+            })
+            .collect()
+    }
+
+    fn check_response(&self, pdu: &Pdu) -> Result<(), SnmpError> {
+        if !self.strict || self.version != Version::V1 {
+            return Ok(());
+        }
+
+        match pdu.bindings().iter().find(|vb| vb.value.is_exception()) {
+            Some(vb) => Err(SnmpError::Decode(format!(
+                "SNMPv1 response carries {:?} for {}",
+                vb.value,
+                ObjectIdentifierDisplayWrapper(&vb.name)
+            ))),
+            None => Ok(()),
+        }
+    }
 
-        let mut recv_buf = [0u8; 1500];
-        let size = self.socket.recv(&mut recv_buf).map_err(|_| -3)?;
-        let msg = decode::<Message>(&recv_buf[..size]).map_err(|_| -4)?;
+    fn record_response(&mut self) {
+        let now = self.now();
+        self.last_response_at = Some(now);
+        self.last_latency = self.last_sent_at.map(|sent| now.duration_since(sent));
+        self.consecutive_timeouts = 0;
+    }
 
-        Ok(msg.data().bindings().to_vec())
+    pub(crate) fn decode_limits(&self) -> DecodeLimits {
+        self.decode_limits
     }
 
-    fn increment_request(&mut self) -> i32 {
+    pub(crate) fn increment_request(&mut self) -> i32 {
         let request = self.current_request;
-        self.current_request += 1;
+        self.current_request = self.current_request.wrapping_add(1);
         request
     }
 }
+
+impl<'a> Client<'a> {
+    /// Opens a socket for a `clone_with` of this client: bound to the same
+    /// local address on a new port and connected to the same agent, if this
+    /// client's socket is connected.
+    ///
+    /// The socket is a new one rather than a `UdpSocket::try_clone`, which
+    /// would share the receive queue: each client would read responses meant
+    /// for the other and drop them for their request-id.
+    ///
+    /// The clone therefore sends from a different source port than this
+    /// client. Agents that only accept managers by source port, and NAT or
+    /// firewall pinholes opened for the original port, will not answer it.
+    pub fn try_clone_socket(&self) -> Result<UdpSocket, SnmpError> {
+        let local = self.socket.local_addr().map_err(SnmpError::Connect)?;
+        let socket = UdpSocket::bind((local.ip(), 0)).map_err(SnmpError::Connect)?;
+        if let Ok(agent) = self.socket.peer_addr() {
+            socket.connect(agent).map_err(SnmpError::Connect)?;
+        }
+        Ok(socket)
+    }
+}
+
+fn response_bindings(version: Version, pdu: &Pdu) -> Result<Vec<VarBinding>, SnmpError> {
+    if let Err(status) = pdu.error() {
+        return Err(SnmpError::Agent {
+            status: ErrorStatus::for_version(status, version),
+            index: pdu.error_index(),
+            oid: pdu.error_binding().map(|vb| vb.name.clone()),
+        });
+    }
+
+    Ok(pdu.bindings().to_vec())
+}
+
+/// Whether `pdu` answers all `requested` bindings with actual values.
+fn is_complete(pdu: &Pdu, requested: usize) -> bool {
+    pdu.bindings().len() >= requested
+        && pdu
+            .bindings()
+            .iter()
+            .all(|vb| vb.value != Value::Null && !vb.value.is_exception())
+}
+
+/// `oids` without repeats, keeping the first occurrence of each.
+fn distinct(oids: &[ObjectIdentifier]) -> Vec<ObjectIdentifier> {
+    let mut seen = Vec::with_capacity(oids.len());
+    for oid in oids {
+        if !seen.contains(oid) {
+            seen.push(oid.clone());
+        }
+    }
+    seen
+}
+
+/// On a connected UDP socket an ICMP port-unreachable from the agent's host
+/// surfaces as `ConnectionRefused` on the next send or receive. Linux and the
+/// BSDs report it this way; other platforms may only ever time out.
+fn send_error(e: io::Error) -> SnmpError {
+    match e.kind() {
+        io::ErrorKind::ConnectionRefused => SnmpError::PortUnreachable,
+        _ => SnmpError::Send(e),
+    }
+}
+
+/// A timeout after only replies from unaccepted addresses arrived is reported
+/// as such, since the agent is then likely answering from another address.
+fn timeout_error(rejected: Option<SocketAddr>) -> SnmpError {
+    match rejected {
+        Some(peer) => SnmpError::UnexpectedSource(peer),
+        None => SnmpError::Timeout,
+    }
+}
+
+fn incomplete_message(total: usize) -> SnmpError {
+    SnmpError::MessageTooLarge(format!("could not reassemble a {} byte message", total))
+}
+
+fn recv_error(e: io::Error) -> SnmpError {
+    match e.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => SnmpError::Timeout,
+        io::ErrorKind::ConnectionRefused => SnmpError::PortUnreachable,
+        _ => SnmpError::Recv(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::oid;
+    use crate::testing::*;
+    use crate::types::ObjectIdentifierExt;
+
+    use std::sync::mpsc;
+    use std::thread;
+
+    #[test]
+    fn exchange_returns_matching_response() {
+        let mut socket = agent_with_replies(|request| {
+            let stale =
+                Pdu::new(PduTag::GetResponse, request.data().request_id() - 1).with_error(5, 0);
+            let report = Pdu::new(PduTag::Report, request.data().request_id())
+                .with_error(5, 1)
+                .with_bindings(request.data().bindings());
+            vec![
+                Message::with_raw_community(request.version(), request.community_bytes(), stale),
+                Message::new(request.version(), "other", report),
+            ]
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        let pdu = Pdu::new(PduTag::GetRequest, 42).with_null_bindings(&[oid! {1,3,6,1}]);
+        let r = c
+            .exchange(Message::new(Version::V1, "secret", pdu))
+            .unwrap();
+        assert_eq!(r.version(), Version::V1);
+        assert_eq!(r.community(), "other");
+        assert_eq!(r.data().tag(), PduTag::Report);
+        assert_eq!(r.data().request_id(), 42);
+        assert_eq!(r.data().error(), Err(5));
+        assert_eq!(r.data().bindings()[0].name, oid! {1,3,6,1});
+    }
+
+    #[test]
+    fn set_uses_write_community() {
+        let (tx, rx) = mpsc::channel();
+        let mut socket = agent(move |request| {
+            tx.send((request.data().tag(), request.community().into_owned()))
+                .unwrap();
+            response(request, request.data().bindings())
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_communities("reader", "writer");
+
+        c.set(&[VarBinding::new(oid! {1,3,6,1,2,1,1,5,0}, Value::Integer(1))])
+            .unwrap();
+        assert_eq!(
+            rx.recv().unwrap(),
+            (PduTag::SetRequest, "writer".to_owned())
+        );
+
+        c.get(&[oid! {1,3,6,1,2,1,1,5,0}]).unwrap();
+        assert_eq!(
+            rx.recv().unwrap(),
+            (PduTag::GetRequest, "reader".to_owned())
+        );
+    }
+
+    /// An agent that answers each request with its response split in two
+    /// datagrams, of which only the first `pieces` are sent.
+    fn fragmenting_agent(pieces: usize) -> UdpSocket {
+        raw_agent(move |buf| {
+            let request = Message::from_bytes(buf).unwrap();
+            let bindings = [VarBinding::new(
+                oid! {1,3,6,1,2,1,1,1,0},
+                Value::from("x".repeat(100).as_str()),
+            )];
+            let b = response(&request, &bindings).to_bytes().unwrap();
+            let (first, second) = b.split_at(b.len() / 2);
+            vec![first.to_vec(), second.to_vec()]
+                .into_iter()
+                .take(pieces)
+                .collect()
+        })
+    }
+
+    #[test]
+    fn fragmented_response_is_reassembled() {
+        let mut socket = fragmenting_agent(2);
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_timeout(Some(Duration::from_secs(1)));
+
+        let r = c.get(&[oid! {1,3,6,1,2,1,1,1,0}]).unwrap();
+        assert_eq!(r[0].value.as_string().unwrap(), "x".repeat(100));
+    }
+
+    #[test]
+    fn large_datagram_is_received_whole() {
+        let descr = "x".repeat(4000);
+        let mut socket = agent(get_agent(vec![VarBinding::new(
+            oid! {1,3,6,1,2,1,1,1,0},
+            Value::from(descr.as_str()),
+        )]));
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_timeout(Some(Duration::from_secs(1)));
+
+        let r = c.get(&[oid! {1,3,6,1,2,1,1,1,0}]).unwrap();
+        assert_eq!(r[0].value.as_string().unwrap(), descr);
+
+        c.send_request(
+            PduTag::GetRequest,
+            &[VarBinding::null_from(oid! {1,3,6,1,2,1,1,1,0})],
+        )
+        .unwrap();
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        let vars = loop {
+            if let Some(vars) = c.poll_response().unwrap() {
+                break vars;
+            }
+            assert!(std::time::Instant::now() < deadline);
+            std::thread::sleep(Duration::from_millis(1));
+        };
+        assert_eq!(vars[0].value.as_string().unwrap(), descr);
+    }
+
+    #[test]
+    fn incomplete_fragmented_response_is_too_large() {
+        let mut socket = fragmenting_agent(1);
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_timeout(Some(Duration::from_millis(50)));
+
+        let r = c.get(&[oid! {1,3,6,1,2,1,1,1,0}]);
+        assert!(matches!(r, Err(SnmpError::MessageTooLarge(_))));
+
+        // Without a timeout the remainder is still only waited for so long.
+        let mut socket = fragmenting_agent(1);
+        let mut c = Client::new(Version::V2C, &mut socket);
+        let r = c.get(&[oid! {1,3,6,1,2,1,1,1,0}]);
+        assert!(matches!(r, Err(SnmpError::MessageTooLarge(_))));
+    }
+
+    #[test]
+    fn reassembled_response_must_answer_the_request() {
+        let mut socket =
+            raw_agent(|buf| {
+                let request = Message::from_bytes(buf).unwrap();
+                let pdu = Pdu::new(PduTag::GetResponse, request.data().request_id() + 1)
+                    .with_bindings(&[VarBinding::new(
+                        oid! {1,3,6,1,2,1,1,1,0},
+                        Value::from("x".repeat(100).as_str()),
+                    )]);
+                let b = Message::new(Version::V2C, "public", pdu)
+                    .to_bytes()
+                    .unwrap();
+                let (first, second) = b.split_at(b.len() / 2);
+                vec![first.to_vec(), second.to_vec()]
+            });
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_timeout(Some(Duration::from_secs(1)));
+
+        let r = c.get(&[oid! {1,3,6,1,2,1,1,1,0}]);
+        assert!(matches!(r, Err(SnmpError::MessageTooLarge(_))));
+    }
+
+    #[test]
+    fn request_ids_count_up_from_start() {
+        let (tx, rx) = mpsc::channel();
+        let mut socket = agent(move |request| {
+            tx.send(request.data().request_id()).unwrap();
+            response(request, request.data().bindings())
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_request_id(i32::MAX);
+
+        c.get(&[oid! {1,3,6,1,2,1,1,5,0}]).unwrap();
+        c.get(&[oid! {1,3,6,1,2,1,1,5,0}]).unwrap();
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), [i32::MAX, i32::MIN]);
+
+        c.set_request_id(100);
+        c.get_next(&[oid! {1,3,6,1,2,1,1,5,0}]).unwrap();
+        c.get_next(&[oid! {1,3,6,1,2,1,1,5,0}]).unwrap();
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), [100, 101]);
+    }
+
+    #[test]
+    fn bulk_get_sets_repetition_fields() {
+        let (tx, rx) = mpsc::channel();
+        let mut socket = agent(move |request| {
+            tx.send(request.data().bulk_repetitions()).unwrap();
+            response(request, request.data().bindings())
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        let r = c
+            .bulk_get(
+                &[oid! {1,3,6,1,2,1,1,3}],
+                20,
+                &[oid! {1,3,6,1,2,1,2,2,1,2}, oid! {1,3,6,1,2,1,2,2,1,10}],
+            )
+            .unwrap();
+        assert_eq!(rx.recv().unwrap(), (1, 20));
+        assert_eq!(r.len(), 3);
+        assert_eq!(r[0].name, oid! {1,3,6,1,2,1,1,3});
+    }
+
+    #[test]
+    fn strict_v1_rejects_exception_values() {
+        // A v1 agent that wrongly answers with a v2c exception.
+        let mut socket = agent(|request| {
+            let bindings: Vec<_> = request
+                .data()
+                .bindings()
+                .iter()
+                .map(|vb| VarBinding::new(vb.name.clone(), Value::NoSuchObject))
+                .collect();
+            response(request, &bindings)
+        });
+        let mut c = Client::new(Version::V1, &mut socket);
+
+        let r = c.get(&[oid! {1,3,6,1,2,1,1,5,0}]).unwrap();
+        assert_eq!(r[0].value, Value::NoSuchObject);
+
+        c.set_strict(true);
+        match c.get(&[oid! {1,3,6,1,2,1,1,5,0}]) {
+            Err(SnmpError::Decode(msg)) => {
+                assert_eq!(
+                    msg,
+                    "SNMPv1 response carries NoSuchObject for 1.3.6.1.2.1.1.5.0"
+                )
+            }
+            r => panic!("unexpected {:?}", r),
+        }
+    }
+
+    #[test]
+    fn error_status_per_version() {
+        fn set_error(version: Version, status: i32) -> SnmpError {
+            let mut socket = agent(move |request| {
+                let pdu = Pdu::new(PduTag::GetResponse, request.data().request_id())
+                    .with_error(status, 1)
+                    .with_bindings(request.data().bindings());
+                Message::with_raw_community(request.version(), request.community_bytes(), pdu)
+            });
+            let mut c = Client::new(version, &mut socket);
+            c.set(&[VarBinding::new(
+                oid! {1,3,6,1,2,1,1,5,0},
+                Value::from("core-1"),
+            )])
+            .unwrap_err()
+        }
+
+        let read_only = set_error(Version::V1, 4);
+        assert!(matches!(
+            read_only,
+            SnmpError::Agent {
+                status: ErrorStatus::ReadOnly,
+                ..
+            }
+        ));
+        assert!(read_only.to_string().contains("readOnly"), "{}", read_only);
+
+        let not_writable = set_error(Version::V2C, 17);
+        assert!(matches!(
+            not_writable,
+            SnmpError::Agent {
+                status: ErrorStatus::NotWritable,
+                ..
+            }
+        ));
+        assert!(
+            not_writable.to_string().contains("notWritable"),
+            "{}",
+            not_writable
+        );
+
+        assert!(matches!(
+            set_error(Version::V1, 17),
+            SnmpError::Agent {
+                status: ErrorStatus::Unknown(17),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn error_index_past_bindings_has_no_oid() {
+        let mut socket = agent(|request| {
+            let pdu = Pdu::new(PduTag::GetResponse, request.data().request_id())
+                .with_error(3, 5)
+                .with_bindings(request.data().bindings());
+            Message::with_raw_community(request.version(), request.community_bytes(), pdu)
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        let r = c.set(&[VarBinding::new(oid! {1,3,6,1,2,1,1,5,0}, Value::Integer(1))]);
+        assert!(matches!(
+            r,
+            Err(SnmpError::Agent {
+                status: ErrorStatus::BadValue,
+                index: 5,
+                oid: None
+            })
+        ));
+    }
+
+    #[test]
+    fn set_one_returns_echoed_value() {
+        let (tx, rx) = mpsc::channel();
+        let mut socket = agent(move |request| {
+            tx.send((request.data().tag(), request.community().into_owned()))
+                .unwrap();
+            response(request, request.data().bindings())
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_communities("reader", "writer");
+
+        let v = c
+            .set_one(&oid! {1,3,6,1,2,1,1,5,0}, Value::from("core-2"))
+            .unwrap();
+        assert_eq!(v, Value::from("core-2"));
+        assert_eq!(
+            rx.recv().unwrap(),
+            (PduTag::SetRequest, "writer".to_owned())
+        );
+    }
+
+    #[test]
+    fn communities_can_be_changed_at_runtime() {
+        let (tx, rx) = mpsc::channel();
+        let mut socket = agent(move |request| {
+            tx.send(request.community().into_owned()).unwrap();
+            response(request, request.data().bindings())
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        for site in 1..=2 {
+            let community = format!("site-{}", site);
+            c.set_communities(&community, &community);
+            drop(community);
+
+            c.get(&[oid! {1,3,6,1,2,1,1,5,0}]).unwrap();
+            assert_eq!(rx.recv().unwrap(), format!("site-{}", site));
+        }
+    }
+
+    #[test]
+    fn get_splits_too_big_requests() {
+        let mib: Vec<_> = (1..=10)
+            .map(|i| VarBinding::new(oid! {1,3,6,1,4,1,1}.child(&[i]), Value::Integer(i as i32)))
+            .collect();
+        let (tx, rx) = mpsc::channel();
+        let mut lookup = get_agent(mib);
+        let mut socket = agent(move |request| {
+            tx.send(request.data().bindings().len()).unwrap();
+            if request.data().bindings().len() > 3 {
+                let pdu = Pdu::new(PduTag::GetResponse, request.data().request_id())
+                    .with_error(1, 0)
+                    .with_bindings(request.data().bindings());
+                return Message::with_raw_community(
+                    request.version(),
+                    request.community_bytes(),
+                    pdu,
+                );
+            }
+            lookup(request)
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        let order = [7, 2, 9, 1, 10, 4, 3, 8, 6, 5];
+        let oids: Vec<_> = order
+            .iter()
+            .map(|i| oid! {1,3,6,1,4,1,1}.child(&[*i]))
+            .collect();
+        let vars = c.get(&oids).unwrap();
+        let values: Vec<_> = vars.iter().map(|vb| vb.value.as_i32().unwrap()).collect();
+        assert_eq!(values, order.iter().map(|i| *i as i32).collect::<Vec<_>>());
+        // 10 -> 5 + 5 -> (2 + 3) + (2 + 3)
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), [10, 5, 2, 3, 5, 2, 3]);
+    }
+
+    #[test]
+    fn value_transform_applies_to_responses() {
+        let mut socket = agent(get_agent(vec![
+            VarBinding::new(oid! {1,3,6,1,4,1,99,1,0}, Value::Integer(21)),
+            VarBinding::new(oid! {1,3,6,1,4,1,99,2,0}, Value::from("probe")),
+        ]));
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_value_transform(Box::new(|_, value| match value {
+            Value::Integer(i) => Value::Integer(i * 2),
+            value => value,
+        }));
+
+        let vars = c
+            .get(&[oid! {1,3,6,1,4,1,99,1,0}, oid! {1,3,6,1,4,1,99,2,0}])
+            .unwrap();
+        assert_eq!(vars[0].value, Value::Integer(42));
+        assert_eq!(vars[1].value, Value::from("probe"));
+    }
+
+    #[test]
+    fn set_non_atomic_splits_too_big_requests() {
+        let (tx, rx) = mpsc::channel();
+        let mut socket = agent(move |request| {
+            let bindings = request.data().bindings();
+            tx.send(bindings.len()).unwrap();
+            let pdu = Pdu::new(PduTag::GetResponse, request.data().request_id());
+            let pdu = match bindings.len() {
+                n if n > 3 => pdu.with_error(1, 0).with_bindings(bindings),
+                _ => pdu.with_bindings(bindings),
+            };
+            Message::with_raw_community(request.version(), request.community_bytes(), pdu)
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        let bindings: Vec<_> = (1..=8)
+            .map(|i| VarBinding::new(oid! {1,3,6,1,4,1,1}.child(&[i]), Value::Integer(i as i32)))
+            .collect();
+        assert!(matches!(
+            c.set(&bindings),
+            Err(SnmpError::Agent {
+                status: ErrorStatus::TooBig,
+                ..
+            })
+        ));
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), [8]);
+
+        let vars = c.set_non_atomic(&bindings).unwrap();
+        let pairs = |vars: &[VarBinding]| vars.iter().map(|vb| vb.to_pair()).collect::<Vec<_>>();
+        assert_eq!(pairs(&vars), pairs(&bindings));
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), [8, 4, 2, 2, 4, 2, 2]);
+    }
+
+    #[test]
+    fn lenient_errors_ignore_stray_error_status() {
+        let mut lookup = get_agent(vec![VarBinding::new(
+            oid! {1,3,6,1,2,1,1,5,0},
+            Value::from("core-1"),
+        )]);
+        let mut socket = agent(move |request| {
+            let response = lookup(request);
+            let pdu = Pdu::new(PduTag::GetResponse, request.data().request_id())
+                .with_error(5, 1)
+                .with_bindings(response.data().bindings());
+            Message::with_raw_community(request.version(), request.community_bytes(), pdu)
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+        let found = [oid! {1,3,6,1,2,1,1,5,0}];
+        let missing = [oid! {1,3,6,1,2,1,1,6,0}];
+
+        let r = c.get(&found);
+        assert!(matches!(
+            r,
+            Err(SnmpError::Agent {
+                status: ErrorStatus::GenErr,
+                ..
+            })
+        ));
+
+        c.set_lenient_errors(true);
+        assert_eq!(c.get(&found).unwrap()[0].value, Value::from("core-1"));
+        assert!(matches!(c.get(&missing), Err(SnmpError::Agent { .. })));
+        let r = c.set(&[VarBinding::new(found[0].clone(), Value::from("core-2"))]);
+        assert!(matches!(r, Err(SnmpError::Agent { .. })));
+    }
+
+    #[test]
+    fn repeated_oids_requested_once() {
+        let sys_name = oid! {1,3,6,1,2,1,1,5,0};
+        let sys_location = oid! {1,3,6,1,2,1,1,6,0};
+        let (tx, rx) = mpsc::channel();
+        let mut lookup = get_agent(vec![
+            VarBinding::new(sys_name.clone(), Value::from("core-1")),
+            VarBinding::new(sys_location.clone(), Value::from("lab")),
+        ]);
+        let mut socket = agent(move |request| {
+            let names: Vec<_> = request
+                .data()
+                .bindings()
+                .iter()
+                .map(|vb| vb.name.clone())
+                .collect();
+            tx.send(names).unwrap();
+            lookup(request)
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        let oids = [
+            sys_location.clone(),
+            sys_name.clone(),
+            sys_location.clone(),
+            sys_name.clone(),
+        ];
+        let vars: Vec<_> = c
+            .get(&oids)
+            .unwrap()
+            .iter()
+            .map(|vb| vb.to_pair())
+            .collect();
+        assert_eq!(
+            vars,
+            [
+                ("1.3.6.1.2.1.1.6.0".to_owned(), Value::from("lab")),
+                ("1.3.6.1.2.1.1.5.0".to_owned(), Value::from("core-1")),
+            ]
+        );
+        assert_eq!(rx.try_recv().unwrap(), vec![sys_location, sys_name]);
+    }
+
+    #[test]
+    fn send_request_and_poll_response() {
+        let mut socket = agent(get_agent(vec![VarBinding::new(
+            oid! {1,3,6,1,2,1,1,5,0},
+            Value::Integer(5),
+        )]));
+        let mut c = Client::new(Version::V2C, &mut socket);
+        assert!(c.poll_response().unwrap().is_none());
+
+        c.send_request(
+            PduTag::GetRequest,
+            &[VarBinding::null_from(oid! {1,3,6,1,2,1,1,5,0})],
+        )
+        .unwrap();
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        let vars = loop {
+            if let Some(vars) = c.poll_response().unwrap() {
+                break vars;
+            }
+            assert!(std::time::Instant::now() < deadline);
+            std::thread::sleep(Duration::from_millis(1));
+        };
+        assert_eq!(vars[0].value, Value::Integer(5));
+        assert!(c.poll_response().unwrap().is_none());
+
+        // The socket is back in blocking mode for ordinary requests.
+        let vars = c.get(&[oid! {1,3,6,1,2,1,1,5,0}]).unwrap();
+        assert_eq!(vars[0].value, Value::Integer(5));
+    }
+
+    #[test]
+    fn send_request_is_unsupported_in_v3() {
+        let mut transport = MockTransport::new(|_| vec![]);
+        let mut c = Client::new(Version::V3, &mut transport);
+
+        let r = c.send_request(PduTag::GetRequest, &[VarBinding::null_from(oid! {1,3,6,1})]);
+        assert!(matches!(r, Err(SnmpError::Unsupported(_))));
+        assert!(c.poll_response().unwrap().is_none());
+        assert!(transport.sent.is_empty());
+    }
+
+    #[test]
+    fn poll_response_does_not_block() {
+        let mut socket = agent_with_replies(|_| vec![]);
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        c.send_request(PduTag::GetRequest, &[VarBinding::null_from(oid! {1,3,6,1})])
+            .unwrap();
+        assert!(c.poll_response().unwrap().is_none());
+    }
+
+    #[test]
+    fn timeout_is_retried() {
+        let (tx, rx) = mpsc::channel();
+        let mut socket = agent_with_replies(move |_| {
+            tx.send(()).unwrap();
+            vec![]
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_timeout(Some(Duration::from_millis(50)));
+        c.set_retries(2);
+
+        let r = c.get(&[oid! {1,3,6,1,2,1,1,5,0}]);
+        assert!(matches!(r, Err(SnmpError::Timeout)));
+        assert_eq!(rx.try_iter().count(), 3);
+    }
+
+    #[test]
+    fn mock_transport_retransmits_and_skips_stale_responses() {
+        let mut lookup = get_agent(vec![VarBinding::new(
+            oid! {1,3,6,1,2,1,1,5,0},
+            Value::from("core-1"),
+        )]);
+        let mut attempts = 0;
+        let mut transport = MockTransport::agent(move |request| {
+            attempts += 1;
+            if attempts == 1 {
+                return vec![];
+            }
+
+            let stale = Pdu::new(PduTag::GetResponse, request.data().request_id() - 1);
+            vec![
+                Message::with_raw_community(request.version(), request.community_bytes(), stale),
+                lookup(request),
+            ]
+        });
+        let mut c = Client::new(Version::V2C, &mut transport);
+        c.set_request_id(100);
+        c.set_retries(1);
+
+        let vars = c.get(&[oid! {1,3,6,1,2,1,1,5,0}]).unwrap();
+        assert_eq!(vars[0].value, Value::from("core-1"));
+
+        assert_eq!(transport.sent.len(), 2);
+        assert_eq!(transport.sent[0], transport.sent[1]);
+        let request = Message::from_bytes(&transport.sent[0]).unwrap();
+        assert_eq!(request.data().request_id(), 100);
+    }
+
+    #[test]
+    fn response_and_timeout_tracking() {
+        let mut lookup = get_agent(vec![VarBinding::new(
+            oid! {1,3,6,1,2,1,1,5,0},
+            Value::from("core-1"),
+        )]);
+        let mut answers = vec![true, false, false, false, true].into_iter();
+        let mut transport = MockTransport::agent(move |request| match answers.next() {
+            Some(true) => vec![lookup(request)],
+            _ => vec![],
+        });
+        let clock = MockClock::new();
+        let mut c = Client::new(Version::V2C, &mut transport);
+        c.set_clock(clock.clone());
+        let oids = [oid! {1,3,6,1,2,1,1,5,0}];
+        assert_eq!(c.last_response_at(), None);
+
+        c.get(&oids).unwrap();
+        let first = clock.now();
+        assert_eq!(c.last_response_at(), Some(first));
+        assert_eq!(c.consecutive_timeouts(), 0);
+
+        clock.advance(Duration::from_secs(10));
+        assert!(matches!(c.get(&oids), Err(SnmpError::Timeout)));
+        assert_eq!(c.consecutive_timeouts(), 1);
+
+        c.set_retries(1);
+        assert!(matches!(c.get(&oids), Err(SnmpError::Timeout)));
+        assert_eq!(c.consecutive_timeouts(), 3);
+        assert_eq!(c.last_response_at(), Some(first));
+
+        c.get(&oids).unwrap();
+        assert_eq!(c.last_response_at(), Some(clock.now()));
+        assert_eq!(c.consecutive_timeouts(), 0);
+    }
+
+    #[test]
+    fn latency_of_delayed_response() {
+        let clock = MockClock::new();
+        let agent_clock = clock.clone();
+        let mut lookup = get_agent(vec![VarBinding::new(
+            oid! {1,3,6,1,2,1,1,5,0},
+            Value::from("core-1"),
+        )]);
+        let mut transport = MockTransport::agent(move |request| {
+            agent_clock.advance(Duration::from_millis(30));
+            vec![lookup(request)]
+        });
+        let mut c = Client::new(Version::V2C, &mut transport);
+        c.set_clock(clock);
+        assert_eq!(c.last_latency(), None);
+
+        c.get(&[oid! {1,3,6,1,2,1,1,5,0}]).unwrap();
+        assert_eq!(c.last_latency(), Some(Duration::from_millis(30)));
+    }
+
+    #[test]
+    fn response_meta_reports_reply_version_and_community() {
+        let mut lookup = get_agent(vec![VarBinding::new(
+            oid! {1,3,6,1,2,1,1,5,0},
+            Value::from("core-1"),
+        )]);
+        let mut transport = MockTransport::agent(move |request| {
+            // The agent answers every request in SNMPv2c and its own community.
+            let reply = lookup(request);
+            vec![Message::new(Version::V2C, "secret", reply.data().clone())]
+        });
+        let mut c = Client::new(Version::V1, &mut transport);
+        assert_eq!(c.last_response_meta(), None);
+
+        c.get(&[oid! {1,3,6,1,2,1,1,5,0}]).unwrap();
+        assert_eq!(
+            c.last_response_meta(),
+            Some(&ResponseMeta {
+                version: Version::V2C,
+                community: Some(b"secret".to_vec()),
+            })
+        );
+    }
+
+    #[test]
+    fn set_with_empty_echo_succeeds() {
+        let mut socket = agent(|request| response(request, &[]));
+        let mut c = Client::new(Version::V2C, &mut socket);
+        let oid = oid! {1,3,6,1,2,1,1,5,0};
+
+        let vars = c
+            .set(&[VarBinding::new(oid.clone(), Value::from("core-2"))])
+            .unwrap();
+        assert!(vars.is_empty());
+        assert_eq!(
+            c.set_one(&oid, Value::from("core-2")).unwrap(),
+            Value::from("core-2")
+        );
+
+        // A failure without bindings is still a failure.
+        let mut socket = agent(|request| {
+            let pdu = Pdu::new(PduTag::GetResponse, request.data().request_id()).with_error(17, 1);
+            Message::with_raw_community(request.version(), request.community_bytes(), pdu)
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+        assert!(matches!(
+            c.set_one(&oid, Value::from("core-2")),
+            Err(SnmpError::Agent {
+                status: ErrorStatus::NotWritable,
+                oid: None,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn rate_limit_spaces_requests() {
+        let clock = MockClock::new();
+        let agent_clock = clock.clone();
+        let (tx, rx) = mpsc::channel();
+        let mut lookup = get_agent(vec![VarBinding::new(
+            oid! {1,3,6,1,2,1,1,5,0},
+            Value::from("core-1"),
+        )]);
+        let mut transport = MockTransport::agent(move |request| {
+            tx.send(agent_clock.now()).unwrap();
+            vec![lookup(request)]
+        });
+        let mut c = Client::new(Version::V2C, &mut transport);
+        c.set_clock(clock.clone());
+        c.set_rate_limit(Some(4.0)).unwrap();
+
+        let start = clock.now();
+        for _ in 0..4 {
+            c.get(&[oid! {1,3,6,1,2,1,1,5,0}]).unwrap();
+        }
+        let offsets: Vec<_> = rx.try_iter().map(|at| at - start).collect();
+        assert_eq!(
+            offsets,
+            [0, 250, 500, 750].map(Duration::from_millis).to_vec()
+        );
+
+        clock.advance(Duration::from_secs(1));
+        let idle = clock.now();
+        c.get(&[oid! {1,3,6,1,2,1,1,5,0}]).unwrap();
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), [idle]);
+
+        c.set_rate_limit(None).unwrap();
+        c.get(&[oid! {1,3,6,1,2,1,1,5,0}]).unwrap();
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), [idle]);
+    }
+
+    #[test]
+    fn invalid_rate_limits_are_refused() {
+        let mut transport = MockTransport::new(|_| vec![]);
+        let mut c = Client::new(Version::V2C, &mut transport);
+        for rate in [0.0, -1.0, f64::NAN, 1e-20] {
+            assert!(matches!(
+                c.set_rate_limit(Some(rate)),
+                Err(SnmpError::InvalidRateLimit(_))
+            ));
+        }
+        assert!(c.set_rate_limit(Some(f64::INFINITY)).is_ok());
+    }
+
+    #[test]
+    fn retry_policy_delays() {
+        let fixed = RetryPolicy::Fixed(Duration::from_millis(300));
+        assert_eq!(fixed.delay(0), Duration::from_millis(300));
+        assert_eq!(fixed.delay(5), Duration::from_millis(300));
+
+        let exponential = RetryPolicy::Exponential {
+            base: Duration::from_millis(100),
+            factor: 2,
+            max: Duration::from_millis(500),
+        };
+        let delays: Vec<_> = (0..5).map(|attempt| exponential.delay(attempt)).collect();
+        assert_eq!(
+            delays,
+            [100, 200, 400, 500, 500]
+                .iter()
+                .map(|ms| Duration::from_millis(*ms))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(exponential.delay(u32::MAX), Duration::from_millis(500));
+    }
+
+    /// Spawns an agent that answers from a socket bound to `reply_from`
+    /// rather than the one requests arrive on, and returns its address.
+    fn agent_replying_from(reply_from: &str) -> SocketAddr {
+        let agent = UdpSocket::bind("127.0.0.1:0").unwrap();
+        agent
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let replies = UdpSocket::bind(reply_from).unwrap();
+        let addr = agent.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let mut buf = [0u8; 1500];
+            while let Ok((size, peer)) = agent.recv_from(&mut buf) {
+                let request = Message::from_bytes(&buf[..size]).unwrap();
+                let b = response(&request, request.data().bindings());
+                replies.send_to(&b.to_bytes().unwrap(), peer).unwrap();
+            }
+        });
+
+        addr
+    }
+
+    #[test]
+    fn unconnected_accepts_reply_from_other_port() {
+        let agent = agent_replying_from("127.0.0.1:0");
+        let mut socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_unconnected_agent(agent, ReplySource::AgentIp);
+        c.set_timeout(Some(Duration::from_secs(1)));
+
+        let r = c.get(&[oid! {1,3,6,1,2,1,1,5,0}]).unwrap();
+        assert_eq!(r[0].name, oid! {1,3,6,1,2,1,1,5,0});
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn unconnected_rejects_reply_from_other_ip() {
+        let agent = agent_replying_from("127.0.0.2:0");
+        let mut socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_unconnected_agent(agent, ReplySource::AgentIp);
+        c.set_timeout(Some(Duration::from_millis(100)));
+
+        match c.get(&[oid! {1,3,6,1,2,1,1,5,0}]) {
+            Err(SnmpError::UnexpectedSource(peer)) => {
+                assert_eq!(peer.ip().to_string(), "127.0.0.2")
+            }
+            r => panic!("unexpected {:?}", r),
+        }
+
+        c.set_unconnected_agent(agent, ReplySource::Any);
+        assert!(c.get(&[oid! {1,3,6,1,2,1,1,5,0}]).is_ok());
+    }
+
+    #[test]
+    fn timeout_is_measured_with_the_clock() {
+        let clock = MockClock::new();
+        let agent_clock = clock.clone();
+        // Only answers to earlier requests arrive, five seconds apart.
+        let mut socket = agent_with_replies(move |request| {
+            (1..=3)
+                .map(|i| {
+                    agent_clock.advance(Duration::from_secs(5));
+                    let pdu = Pdu::new(
+                        PduTag::GetResponse,
+                        request.data().request_id().wrapping_sub(i),
+                    );
+                    Message::with_raw_community(request.version(), request.community_bytes(), pdu)
+                })
+                .collect()
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_clock(clock);
+        c.set_timeout(Some(Duration::from_secs(10)));
+
+        let start = std::time::Instant::now();
+        let r = c.get(&[oid! {1,3,6,1,2,1,1,5,0}]);
+        assert!(matches!(r, Err(SnmpError::Timeout)));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn exponential_retries_wait_longer() {
+        let mut socket = agent_with_replies(|_| vec![]);
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_retry_policy(RetryPolicy::Exponential {
+            base: Duration::from_millis(40),
+            factor: 2,
+            max: Duration::from_secs(1),
+        });
+        c.set_retries(2);
+
+        let start = std::time::Instant::now();
+        let r = c.get(&[oid! {1,3,6,1,2,1,1,5,0}]);
+        assert!(matches!(r, Err(SnmpError::Timeout)));
+        // 40 + 80 + 160ms
+        assert!(start.elapsed() >= Duration::from_millis(280));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn port_unreachable_is_not_retried() {
+        let closed = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.connect(closed.local_addr().unwrap()).unwrap();
+        drop(closed);
+
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_timeout(Some(Duration::from_secs(5)));
+        c.set_retries(3);
+
+        let start = std::time::Instant::now();
+        let r = c.get(&[oid! {1,3,6,1,2,1,1,5,0}]);
+        assert!(matches!(r, Err(SnmpError::PortUnreachable)));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn v3_only_rejects_community_versions() {
+        let mut socket = local_socket();
+        let mut c = Client::new(Version::V2C, &mut socket).v3_only();
+        c.set_communities("public", "private");
+
+        let r = c.get(&[oid! {1,3,6,1}]);
+        assert!(matches!(r, Err(SnmpError::CommunityDisabled(Version::V2C))));
+    }
+
+    #[test]
+    fn cloned_clients_poll_from_two_threads() {
+        let name = oid! {1,3,6,1,2,1,1,5,0};
+        let mut socket = agent(get_agent(vec![VarBinding::new(
+            name.clone(),
+            Value::from("core-1"),
+        )]));
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_timeout(Some(Duration::from_secs(5)));
+        let mut other_socket = c.try_clone_socket().unwrap();
+        let mut other = c.clone_with(&mut other_socket);
+        assert_eq!(other.retry_policy(), c.retry_policy());
+
+        let poll = |client: &mut Client<'_>| {
+            (0..20)
+                .filter(|_| client.get(std::slice::from_ref(&name)).is_ok())
+                .count()
+        };
+        thread::scope(|s| {
+            let first = s.spawn(|| poll(&mut c));
+            let second = s.spawn(|| poll(&mut other));
+            assert_eq!(first.join().unwrap(), 20);
+            assert_eq!(second.join().unwrap(), 20);
+        });
+    }
+}
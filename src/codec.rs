@@ -0,0 +1,49 @@
+//! The encoding rules messages are written and read with.
+
+use crate::error::SnmpError;
+
+use rasn::{Decode, Encode};
+
+use std::fmt;
+
+/// A set of ASN.1 encoding rules. Every message, PDU and nested structure is
+/// encoded and decoded through one, so errors are mapped the same way
+/// everywhere and any quirk an agent needs has a single place to go.
+pub(crate) trait Codec {
+    type EncodeError: fmt::Display;
+    type DecodeError: fmt::Display;
+
+    /// Encodes `value`, for use inside an `Encode` implementation.
+    fn encode_raw<T: Encode>(value: &T) -> Result<Vec<u8>, Self::EncodeError>;
+
+    /// Decodes a `T`, for use inside a `Decode` implementation.
+    fn decode_raw<T: Decode>(bytes: &[u8]) -> Result<T, Self::DecodeError>;
+
+    fn encode<T: Encode>(value: &T) -> Result<Vec<u8>, SnmpError> {
+        Self::encode_raw(value).map_err(|e| SnmpError::Encode(e.to_string()))
+    }
+
+    fn decode<T: Decode>(bytes: &[u8]) -> Result<T, SnmpError> {
+        Self::decode_raw(bytes).map_err(|e| SnmpError::Decode(e.to_string()))
+    }
+}
+
+/// The Basic Encoding Rules SNMP is defined with (RFC 3417 8).
+///
+/// Encoding is deterministic: definite lengths in their shortest form, so the
+/// same message always encodes to the same bytes. SNMPv3 authentication
+/// depends on this, as the digest is computed over the encoded message.
+pub(crate) struct Ber;
+
+impl Codec for Ber {
+    type EncodeError = rasn::ber::enc::Error;
+    type DecodeError = rasn::ber::de::Error;
+
+    fn encode_raw<T: Encode>(value: &T) -> Result<Vec<u8>, Self::EncodeError> {
+        rasn::ber::encode(value)
+    }
+
+    fn decode_raw<T: Decode>(bytes: &[u8]) -> Result<T, Self::DecodeError> {
+        rasn::ber::decode(bytes)
+    }
+}
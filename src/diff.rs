@@ -0,0 +1,142 @@
+//! Comparing two snapshots of the same subtree.
+
+use crate::types::{ObjectIdentifier, Value, VarBinding};
+
+use std::collections::BTreeMap;
+
+/// A difference between two snapshots, for one OID.
+#[derive(Clone, Debug)]
+pub enum Change {
+    /// The OID is only in the new snapshot.
+    Added(VarBinding),
+    /// The OID is only in the old snapshot.
+    Removed(ObjectIdentifier),
+    /// The OID is in both snapshots with different values.
+    Modified {
+        oid: ObjectIdentifier,
+        old: Value,
+        new: Value,
+    },
+}
+
+impl Change {
+    pub fn oid(&self) -> &ObjectIdentifier {
+        match self {
+            Change::Added(vb) => &vb.name,
+            Change::Removed(oid) => oid,
+            Change::Modified { oid, .. } => oid,
+        }
+    }
+}
+
+/// The changes from `old` to `new`, such as two walks of a table, in OID
+/// order. Neither needs to be sorted; if an OID appears more than once, its
+/// last binding counts.
+///
+/// Counters are compared by number: a `Counter64` is unchanged when the agent
+/// next sends the same count wrapped in an `Opaque`, or as a `Counter32`.
+pub fn diff_bindings(old: &[VarBinding], new: &[VarBinding]) -> Vec<Change> {
+    let mut old = by_name(old);
+    let mut changes = vec![];
+
+    for (name, vb) in by_name(new) {
+        match old.remove(name) {
+            None => changes.push(Change::Added(vb.clone())),
+            Some(before) if !same_value(&before.value, &vb.value) => {
+                changes.push(Change::Modified {
+                    oid: vb.name.clone(),
+                    old: before.value.clone(),
+                    new: vb.value.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    changes.extend(old.into_values().map(|vb| Change::Removed(vb.name.clone())));
+
+    changes.sort_by(|a, b| a.oid().as_slice().cmp(b.oid().as_slice()));
+    changes
+}
+
+fn by_name(vbs: &[VarBinding]) -> BTreeMap<&[u32], &VarBinding> {
+    vbs.iter().map(|vb| (vb.name.as_slice(), vb)).collect()
+}
+
+fn same_value(old: &Value, new: &Value) -> bool {
+    match (counter(old), counter(new)) {
+        (Some(a), Some(b)) => a == b,
+        _ => old == new,
+    }
+}
+
+fn counter(value: &Value) -> Option<u64> {
+    match value {
+        Value::Counter32(v) => Some(u64::from(*v)),
+        _ => value.as_counter64(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oid;
+    use crate::types::ObjectIdentifierExt;
+
+    fn if_oper_status(index: u32, status: i32) -> VarBinding {
+        VarBinding::new(
+            oid! {1,3,6,1,2,1,2,2,1,8}.child(&[index]),
+            Value::Integer(status),
+        )
+    }
+
+    #[test]
+    fn added_removed_and_modified() {
+        let old = vec![
+            if_oper_status(3, 1),
+            if_oper_status(1, 1),
+            if_oper_status(2, 1),
+        ];
+        let new = vec![
+            if_oper_status(1, 1),
+            if_oper_status(2, 2),
+            if_oper_status(4, 1),
+        ];
+
+        let changes = diff_bindings(&old, &new);
+        assert_eq!(changes.len(), 3);
+        match &changes[0] {
+            Change::Modified { oid, old, new } => {
+                assert_eq!(oid, &if_oper_status(2, 0).name);
+                assert_eq!(old, &Value::Integer(1));
+                assert_eq!(new, &Value::Integer(2));
+            }
+            change => panic!("unexpected {:?}", change),
+        }
+        match &changes[1] {
+            Change::Removed(oid) => assert_eq!(oid, &if_oper_status(3, 0).name),
+            change => panic!("unexpected {:?}", change),
+        }
+        match &changes[2] {
+            Change::Added(vb) => assert_eq!(vb.to_pair(), if_oper_status(4, 1).to_pair()),
+            change => panic!("unexpected {:?}", change),
+        }
+
+        assert!(diff_bindings(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn counters_compare_by_number() {
+        let name = oid! {1,3,6,1,2,1,31,1,1,1,6,1};
+        let old = [VarBinding::new(name.clone(), Value::Counter64(5000))];
+
+        let wrapped = [VarBinding::new(name.clone(), Value::opaque_counter64(5000))];
+        assert!(diff_bindings(&old, &wrapped).is_empty());
+        let narrow = [VarBinding::new(name.clone(), Value::Counter32(5000))];
+        assert!(diff_bindings(&old, &narrow).is_empty());
+
+        let gauge = [VarBinding::new(name.clone(), Value::Gauge32(5000))];
+        assert_eq!(diff_bindings(&old, &gauge).len(), 1);
+        let counted = [VarBinding::new(name, Value::Counter64(5001))];
+        assert_eq!(diff_bindings(&old, &counted).len(), 1);
+    }
+}
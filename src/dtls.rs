@@ -0,0 +1,74 @@
+//! SNMP over DTLS (RFC 6353). Only the configuration exists so far: there is
+//! no DTLS backend to run the session, so connecting always fails with
+//! `SnmpError::Unsupported`.
+
+use crate::error::SnmpError;
+use crate::transport::Transport;
+
+use std::io;
+use std::net::UdpSocket;
+
+/// The certificates a DTLS session authenticates with (RFC 6353 5.3).
+/// Certificates and keys are DER-encoded.
+///
+/// Only `DtlsTransport::connect` takes this, and it cannot connect yet; see
+/// there.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DtlsConfig {
+    /// This end's certificate first, then any intermediates.
+    pub certificate_chain: Vec<Vec<u8>>,
+    pub private_key: Option<Vec<u8>>,
+    /// The CA certificates the agent's certificate must chain to.
+    pub trust_anchors: Vec<Vec<u8>>,
+    /// Accepts the agent only if its certificate has this SHA-256
+    /// fingerprint, in place of or as well as `trust_anchors`.
+    pub peer_fingerprint: Option<Vec<u8>>,
+}
+
+/// A `Transport` carrying SNMPv3 messages over a DTLS session.
+///
+/// This is a placeholder until a DTLS backend is chosen: `connect` always
+/// fails with `SnmpError::Unsupported`, so no value of this type can exist.
+/// Do not build a client on it expecting it to connect; nothing has been
+/// tested against a DTLS agent.
+#[derive(Debug)]
+pub struct DtlsTransport(Never);
+
+#[derive(Debug)]
+enum Never {}
+
+impl DtlsTransport {
+    /// Establishes a DTLS session with the agent `socket` is connected to.
+    /// Fails with `Unsupported` until a DTLS backend is chosen.
+    pub fn connect(_socket: UdpSocket, _config: &DtlsConfig) -> Result<DtlsTransport, SnmpError> {
+        Err(SnmpError::Unsupported("SNMP over DTLS"))
+    }
+}
+
+impl Transport for DtlsTransport {
+    fn send(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        match self.0 {}
+    }
+
+    fn recv(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        match self.0 {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::local_socket;
+
+    #[test]
+    fn connect_is_unsupported() {
+        let config = DtlsConfig {
+            peer_fingerprint: Some(vec![0; 32]),
+            ..DtlsConfig::default()
+        };
+        assert!(matches!(
+            DtlsTransport::connect(local_socket(), &config),
+            Err(SnmpError::Unsupported(_))
+        ));
+    }
+}
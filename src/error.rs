@@ -0,0 +1,272 @@
+use crate::types::{ObjectIdentifier, ObjectIdentifierDisplayWrapper, Version};
+use crate::usm::ReportReason;
+
+use rasn::Tag;
+
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+
+/// The error-status field of a response PDU (RFC 3416).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorStatus {
+    NoError,
+    TooBig,
+    NoSuchName,
+    BadValue,
+    ReadOnly,
+    GenErr,
+    NoAccess,
+    WrongType,
+    WrongLength,
+    WrongEncoding,
+    WrongValue,
+    NoCreation,
+    InconsistentValue,
+    ResourceUnavailable,
+    CommitFailed,
+    UndoFailed,
+    AuthorizationError,
+    NotWritable,
+    InconsistentName,
+    Unknown(i32),
+}
+
+impl ErrorStatus {
+    pub fn from_i32(status: i32) -> ErrorStatus {
+        match status {
+            0 => ErrorStatus::NoError,
+            1 => ErrorStatus::TooBig,
+            2 => ErrorStatus::NoSuchName,
+            3 => ErrorStatus::BadValue,
+            4 => ErrorStatus::ReadOnly,
+            5 => ErrorStatus::GenErr,
+            6 => ErrorStatus::NoAccess,
+            7 => ErrorStatus::WrongType,
+            8 => ErrorStatus::WrongLength,
+            9 => ErrorStatus::WrongEncoding,
+            10 => ErrorStatus::WrongValue,
+            11 => ErrorStatus::NoCreation,
+            12 => ErrorStatus::InconsistentValue,
+            13 => ErrorStatus::ResourceUnavailable,
+            14 => ErrorStatus::CommitFailed,
+            15 => ErrorStatus::UndoFailed,
+            16 => ErrorStatus::AuthorizationError,
+            17 => ErrorStatus::NotWritable,
+            18 => ErrorStatus::InconsistentName,
+            _ => ErrorStatus::Unknown(status),
+        }
+    }
+
+    /// Like `from_i32`, but only accepts the codes `version` defines: an
+    /// SNMPv1 PDU has no status past `genErr`, so those are `Unknown` there.
+    pub fn for_version(status: i32, version: Version) -> ErrorStatus {
+        let status = ErrorStatus::from_i32(status);
+        match status {
+            ErrorStatus::Unknown(_) => status,
+            _ if version == Version::V1 && !status.is_v1() => ErrorStatus::Unknown(status.to_i32()),
+            _ => status,
+        }
+    }
+
+    pub fn to_i32(self) -> i32 {
+        match self {
+            ErrorStatus::NoError => 0,
+            ErrorStatus::TooBig => 1,
+            ErrorStatus::NoSuchName => 2,
+            ErrorStatus::BadValue => 3,
+            ErrorStatus::ReadOnly => 4,
+            ErrorStatus::GenErr => 5,
+            ErrorStatus::NoAccess => 6,
+            ErrorStatus::WrongType => 7,
+            ErrorStatus::WrongLength => 8,
+            ErrorStatus::WrongEncoding => 9,
+            ErrorStatus::WrongValue => 10,
+            ErrorStatus::NoCreation => 11,
+            ErrorStatus::InconsistentValue => 12,
+            ErrorStatus::ResourceUnavailable => 13,
+            ErrorStatus::CommitFailed => 14,
+            ErrorStatus::UndoFailed => 15,
+            ErrorStatus::AuthorizationError => 16,
+            ErrorStatus::NotWritable => 17,
+            ErrorStatus::InconsistentName => 18,
+            ErrorStatus::Unknown(status) => status,
+        }
+    }
+
+    /// Whether SNMPv1 (RFC 1157) defines this status; the others were added
+    /// by SNMPv2.
+    pub fn is_v1(&self) -> bool {
+        matches!(
+            self,
+            ErrorStatus::NoError
+                | ErrorStatus::TooBig
+                | ErrorStatus::NoSuchName
+                | ErrorStatus::BadValue
+                | ErrorStatus::ReadOnly
+                | ErrorStatus::GenErr
+        )
+    }
+}
+
+impl fmt::Display for ErrorStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ErrorStatus::NoError => "noError",
+            ErrorStatus::TooBig => "tooBig",
+            ErrorStatus::NoSuchName => "noSuchName",
+            ErrorStatus::BadValue => "badValue",
+            ErrorStatus::ReadOnly => "readOnly",
+            ErrorStatus::GenErr => "genErr",
+            ErrorStatus::NoAccess => "noAccess",
+            ErrorStatus::WrongType => "wrongType",
+            ErrorStatus::WrongLength => "wrongLength",
+            ErrorStatus::WrongEncoding => "wrongEncoding",
+            ErrorStatus::WrongValue => "wrongValue",
+            ErrorStatus::NoCreation => "noCreation",
+            ErrorStatus::InconsistentValue => "inconsistentValue",
+            ErrorStatus::ResourceUnavailable => "resourceUnavailable",
+            ErrorStatus::CommitFailed => "commitFailed",
+            ErrorStatus::UndoFailed => "undoFailed",
+            ErrorStatus::AuthorizationError => "authorizationError",
+            ErrorStatus::NotWritable => "notWritable",
+            ErrorStatus::InconsistentName => "inconsistentName",
+            ErrorStatus::Unknown(status) => return write!(f, "unknown({})", status),
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug)]
+pub enum SnmpError {
+    Encode(String),
+    Decode(String),
+    /// The datagram is not a BER SEQUENCE, so it cannot be an SNMP message.
+    MalformedMessage {
+        found: Tag,
+    },
+    /// A received message exceeds the configured `DecodeLimits`.
+    MessageTooLarge(String),
+    Send(io::Error),
+    Recv(io::Error),
+    /// The socket could not be bound or connected.
+    Connect(io::Error),
+    /// No response arrived within the timeout, including retries.
+    Timeout,
+    /// Instead of a response, an unconnected client only received replies
+    /// from this address, which it does not accept.
+    UnexpectedSource(SocketAddr),
+    /// The agent's host answered with ICMP port-unreachable: it is up, but
+    /// nothing is listening for SNMP.
+    PortUnreachable,
+    /// A community-based (v1/v2c) message was requested from a client that is
+    /// locked to SNMPv3.
+    CommunityDisabled(Version),
+    Unsupported(&'static str),
+    /// The agent answered with a non-zero error-status. `index` is the 1-based
+    /// position of the offending binding, or 0, and `oid` its name if `index`
+    /// is within the response's bindings.
+    Agent {
+        status: ErrorStatus,
+        index: i32,
+        oid: Option<ObjectIdentifier>,
+    },
+    /// `set_checked` refused a value that does not fit the object's syntax.
+    InvalidValue {
+        oid: ObjectIdentifier,
+        reason: String,
+    },
+    /// `Value::display_string` refused text the `DisplayString` convention
+    /// does not allow.
+    InvalidDisplayString(String),
+    /// `Client::set_rate_limit` was given a rate that is not positive, or too
+    /// small to wait between sends for.
+    InvalidRateLimit(f64),
+    /// A walk received an OID that does not follow the one it asked for.
+    NonIncreasingOid(ObjectIdentifier),
+    /// The agent rejected an SNMPv3 request with a Report carrying this
+    /// counter, which is not one of the `usmStats` counters `V3Auth` covers.
+    Report(ObjectIdentifier),
+    /// The agent rejected an SNMPv3 request with a `usmStats` Report: the
+    /// user, its passwords or the security level do not match the agent's
+    /// configuration.
+    V3Auth(ReportReason),
+}
+
+impl fmt::Display for SnmpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnmpError::Encode(msg) => write!(f, "Failed to encode message: {}", msg),
+            SnmpError::Decode(msg) => write!(f, "Failed to decode message: {}", msg),
+            SnmpError::MalformedMessage { found } => write!(
+                f,
+                "Expected a message SEQUENCE, found {:?} tag {}",
+                found.class, found.value
+            ),
+            SnmpError::MessageTooLarge(msg) => write!(f, "Message too large: {}", msg),
+            SnmpError::Send(e) => write!(f, "Failed to send request: {}", e),
+            SnmpError::Recv(e) => write!(f, "Failed to receive response: {}", e),
+            SnmpError::Connect(e) => write!(f, "Failed to open socket: {}", e),
+            SnmpError::Timeout => write!(f, "Timed out waiting for a response"),
+            SnmpError::UnexpectedSource(peer) => write!(
+                f,
+                "Timed out waiting for a response, ignoring replies from {}",
+                peer
+            ),
+            SnmpError::PortUnreachable => write!(f, "Agent port is unreachable"),
+            SnmpError::CommunityDisabled(v) => write!(
+                f,
+                "Client is restricted to SNMPv3, refusing to send a {:?} community message",
+                v
+            ),
+            SnmpError::Unsupported(what) => write!(f, "{} is not supported", what),
+            SnmpError::Agent {
+                status,
+                index,
+                oid: Some(oid),
+            } => write!(
+                f,
+                "Agent returned {} for {} (index {})",
+                status,
+                ObjectIdentifierDisplayWrapper(oid),
+                index
+            ),
+            SnmpError::Agent { status, index, .. } => {
+                write!(f, "Agent returned {} (index {})", status, index)
+            }
+            SnmpError::InvalidValue { oid, reason } => write!(
+                f,
+                "Invalid value for {}: {}",
+                ObjectIdentifierDisplayWrapper(oid),
+                reason
+            ),
+            SnmpError::InvalidDisplayString(reason) => {
+                write!(f, "Invalid DisplayString: {}", reason)
+            }
+            SnmpError::InvalidRateLimit(rate) => {
+                write!(f, "Invalid rate limit of {} requests a second", rate)
+            }
+            SnmpError::NonIncreasingOid(oid) => write!(
+                f,
+                "Agent returned non-increasing OID {}",
+                ObjectIdentifierDisplayWrapper(oid)
+            ),
+            SnmpError::Report(oid) => write!(
+                f,
+                "Agent rejected the request with a report of {}",
+                ObjectIdentifierDisplayWrapper(oid)
+            ),
+            SnmpError::V3Auth(reason) => write!(f, "Agent rejected the credentials: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SnmpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SnmpError::Send(e) | SnmpError::Recv(e) | SnmpError::Connect(e) => Some(e),
+            _ => None,
+        }
+    }
+}
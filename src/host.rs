@@ -0,0 +1,169 @@
+//! Typed helpers for the HOST-RESOURCES-MIB (RFC 2790).
+
+use crate::client::Client;
+use crate::error::SnmpError;
+use crate::table::column_roots;
+use crate::transport::Transport;
+use crate::types::ObjectIdentifier;
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::sync::OnceLock;
+
+const HR_STORAGE_ENTRY: &[u32] = &[1, 3, 6, 1, 2, 1, 25, 2, 3, 1];
+const HR_STORAGE_DESCR: u32 = 3;
+const HR_STORAGE_ALLOCATION_UNITS: u32 = 4;
+const HR_STORAGE_SIZE: u32 = 5;
+const HR_STORAGE_USED: u32 = 6;
+
+const HR_STORAGE_COLUMNS: &[u32] = &[
+    HR_STORAGE_DESCR,
+    HR_STORAGE_ALLOCATION_UNITS,
+    HR_STORAGE_SIZE,
+    HR_STORAGE_USED,
+];
+
+const HR_PROCESSOR_LOAD: &[u32] = &[1, 3, 6, 1, 2, 1, 25, 3, 3, 1, 2];
+
+/// A row of `hrStorageTable`. Columns the agent does not return are `None`.
+/// Sizes are counted in allocation units, not bytes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StorageInfo {
+    pub index: u32,
+    pub descr: Option<String>,
+    /// The size in bytes of one allocation unit.
+    pub alloc_units: Option<i32>,
+    pub size: Option<i32>,
+    pub used: Option<i32>,
+}
+
+impl StorageInfo {
+    pub fn size_bytes(&self) -> Option<u64> {
+        bytes(self.size?, self.alloc_units?)
+    }
+
+    pub fn used_bytes(&self) -> Option<u64> {
+        bytes(self.used?, self.alloc_units?)
+    }
+}
+
+fn bytes(units: i32, unit_size: i32) -> Option<u64> {
+    let units = u64::try_from(units).ok()?;
+    let unit_size = u64::try_from(unit_size).ok()?;
+    units.checked_mul(unit_size)
+}
+
+impl<'a, T: Transport> Client<'a, T> {
+    /// Walks `hrStorageTable`, returning its rows in `hrStorageIndex` order.
+    pub fn storage(&mut self) -> Result<Vec<StorageInfo>, SnmpError> {
+        static ROOTS: OnceLock<Vec<ObjectIdentifier>> = OnceLock::new();
+        let roots = column_roots(&ROOTS, HR_STORAGE_ENTRY, HR_STORAGE_COLUMNS);
+
+        let mut rows = BTreeMap::new();
+        for (column, vars) in HR_STORAGE_COLUMNS.iter().zip(self.walk_many(roots)?) {
+            for var in vars {
+                let index = match var.name.as_slice().strip_prefix(HR_STORAGE_ENTRY) {
+                    Some([_, index]) => *index,
+                    _ => continue,
+                };
+                let row = rows.entry(index).or_insert_with(|| StorageInfo {
+                    index,
+                    ..StorageInfo::default()
+                });
+
+                let value = &var.value;
+                match *column {
+                    HR_STORAGE_DESCR => row.descr = value.as_string(),
+                    HR_STORAGE_ALLOCATION_UNITS => row.alloc_units = value.as_i32(),
+                    HR_STORAGE_SIZE => row.size = value.as_i32(),
+                    HR_STORAGE_USED => row.used = value.as_i32(),
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(rows.into_values().collect())
+    }
+
+    /// Walks `hrProcessorLoad`, returning the `hrDeviceIndex` of every
+    /// processor with its load: the percentage of the last minute it was not
+    /// idle.
+    pub fn processor_loads(&mut self) -> Result<Vec<(u32, i32)>, SnmpError> {
+        let column = ObjectIdentifier::new(HR_PROCESSOR_LOAD.to_vec()).unwrap();
+
+        let loads = self
+            .walk(&column)?
+            .into_iter()
+            .filter_map(
+                |var| match var.name.as_slice().strip_prefix(HR_PROCESSOR_LOAD) {
+                    Some([index]) => Some((*index, var.value.as_i32()?)),
+                    _ => None,
+                },
+            )
+            .collect();
+        Ok(loads)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oid;
+    use crate::testing::*;
+    use crate::types::{ObjectIdentifierExt, Value, VarBinding, Version};
+
+    #[test]
+    fn storage_table() {
+        let column = |c: u32, i: u32| oid! {1,3,6,1,2,1,25,2,3,1}.child(&[c, i]);
+        let mib = vec![
+            VarBinding::new(column(3, 1), Value::from("Physical memory")),
+            VarBinding::new(column(3, 31), Value::from("/")),
+            VarBinding::new(column(4, 1), Value::Integer(1024)),
+            VarBinding::new(column(4, 31), Value::Integer(4096)),
+            VarBinding::new(column(5, 1), Value::Integer(8_000_000)),
+            VarBinding::new(column(5, 31), Value::Integer(25_000_000)),
+            VarBinding::new(column(6, 1), Value::Integer(6_000_000)),
+            VarBinding::new(oid! {1,3,6,1,2,1,25,3,3,1,2,196608}, Value::Integer(7)),
+        ];
+        let mut socket = agent(get_next_agent(mib));
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        let storage = c.storage().unwrap();
+        assert_eq!(
+            storage,
+            vec![
+                StorageInfo {
+                    index: 1,
+                    descr: Some("Physical memory".to_owned()),
+                    alloc_units: Some(1024),
+                    size: Some(8_000_000),
+                    used: Some(6_000_000),
+                },
+                StorageInfo {
+                    index: 31,
+                    descr: Some("/".to_owned()),
+                    alloc_units: Some(4096),
+                    size: Some(25_000_000),
+                    used: None,
+                },
+            ]
+        );
+        assert_eq!(storage[1].size_bytes(), Some(102_400_000_000));
+        assert_eq!(storage[1].used_bytes(), None);
+    }
+
+    #[test]
+    fn processor_loads() {
+        let load = |i: u32| oid! {1,3,6,1,2,1,25,3,3,1,2}.child(&[i]);
+        let mib = vec![
+            VarBinding::new(oid! {1,3,6,1,2,1,25,3,3,1,1,196608}, Value::Oid(oid! {0,0})),
+            VarBinding::new(load(196608), Value::Integer(12)),
+            VarBinding::new(load(196609), Value::Integer(87)),
+            VarBinding::new(oid! {1,3,6,1,2,1,25,4,1,0}, Value::Integer(1)),
+        ];
+        let mut socket = agent(get_next_agent(mib));
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        assert_eq!(c.processor_loads().unwrap(), [(196608, 12), (196609, 87)]);
+    }
+}
@@ -0,0 +1,166 @@
+//! Decoding the table index encoded in the trailing arcs of an instance OID
+//! (RFC 2578 7.7).
+
+use crate::error::SnmpError;
+
+use std::net::Ipv4Addr;
+
+/// The syntax of one component of a table's INDEX clause.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IndexSyntax {
+    /// An INTEGER or unsigned value in a single arc.
+    Integer,
+    /// An IpAddress in four arcs.
+    IpAddress,
+    /// An OCTET STRING, preceded by an arc with its length.
+    String,
+    /// An IMPLIED OCTET STRING taking all remaining arcs. Only allowed last.
+    ImpliedString,
+}
+
+/// A decoded index component.
+#[derive(Clone, Debug, PartialEq)]
+pub enum IndexValue {
+    Integer(u32),
+    IpAddress(Ipv4Addr),
+    /// The octets of the string, as they were in the index. Strings used as
+    /// indices are often not text, e.g. SNMPv3 engine ids.
+    String(Vec<u8>),
+}
+
+impl IndexValue {
+    /// The string as text, if it is a `String` of valid UTF-8.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            IndexValue::String(octets) => std::str::from_utf8(octets).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Splits index arcs into typed components following an INDEX clause.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexDecoder {
+    syntax: Vec<IndexSyntax>,
+}
+
+impl IndexDecoder {
+    pub fn new(syntax: &[IndexSyntax]) -> IndexDecoder {
+        IndexDecoder {
+            syntax: syntax.to_vec(),
+        }
+    }
+
+    /// Decodes `arcs`, the part of an instance OID after its column, which
+    /// must hold exactly the declared components.
+    pub fn decode(&self, arcs: &[u32]) -> Result<Vec<IndexValue>, SnmpError> {
+        let mut rest = arcs;
+        let mut values = vec![];
+        for syntax in &self.syntax {
+            let value = match syntax {
+                IndexSyntax::Integer => IndexValue::Integer(take(&mut rest, 1)?[0]),
+                IndexSyntax::IpAddress => {
+                    let octets = octets(take(&mut rest, 4)?)?;
+                    IndexValue::IpAddress(Ipv4Addr::new(octets[0], octets[1], octets[2], octets[3]))
+                }
+                IndexSyntax::String => {
+                    let len = take(&mut rest, 1)?[0] as usize;
+                    string(take(&mut rest, len)?)?
+                }
+                IndexSyntax::ImpliedString => string(std::mem::take(&mut rest))?,
+            };
+            values.push(value);
+        }
+
+        if !rest.is_empty() {
+            return Err(SnmpError::Decode(format!(
+                "{} index arcs left over",
+                rest.len()
+            )));
+        }
+        Ok(values)
+    }
+}
+
+fn take<'a>(rest: &mut &'a [u32], n: usize) -> Result<&'a [u32], SnmpError> {
+    if rest.len() < n {
+        return Err(SnmpError::Decode(format!(
+            "index needs {} more arcs, {} left",
+            n,
+            rest.len()
+        )));
+    }
+
+    let (taken, tail) = rest.split_at(n);
+    *rest = tail;
+    Ok(taken)
+}
+
+fn octets(arcs: &[u32]) -> Result<Vec<u8>, SnmpError> {
+    arcs.iter()
+        .map(|arc| {
+            if *arc > 255 {
+                return Err(SnmpError::Decode(format!(
+                    "index arc {} is not an octet",
+                    arc
+                )));
+            }
+            Ok(*arc as u8)
+        })
+        .collect()
+}
+
+fn string(arcs: &[u32]) -> Result<IndexValue, SnmpError> {
+    octets(arcs).map(IndexValue::String)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer_index() {
+        let decoder = IndexDecoder::new(&[IndexSyntax::Integer]);
+        assert_eq!(decoder.decode(&[12]).unwrap(), [IndexValue::Integer(12)]);
+        assert!(decoder.decode(&[]).is_err());
+        assert!(decoder.decode(&[1, 2]).is_err());
+    }
+
+    #[test]
+    fn ip_address_index() {
+        // ipNetToMediaTable: INDEX { ipNetToMediaIfIndex, ipNetToMediaNetAddress }
+        let decoder = IndexDecoder::new(&[IndexSyntax::Integer, IndexSyntax::IpAddress]);
+        assert_eq!(
+            decoder.decode(&[2, 10, 0, 0, 1]).unwrap(),
+            [
+                IndexValue::Integer(2),
+                IndexValue::IpAddress(Ipv4Addr::new(10, 0, 0, 1))
+            ]
+        );
+        assert!(decoder.decode(&[2, 10, 0, 0]).is_err());
+        assert!(decoder.decode(&[2, 10, 0, 0, 256]).is_err());
+    }
+
+    #[test]
+    fn string_indices() {
+        let decoder = IndexDecoder::new(&[IndexSyntax::String, IndexSyntax::ImpliedString]);
+        let arcs = [5, 97, 100, 109, 105, 110, 114, 111];
+        assert_eq!(
+            decoder.decode(&arcs).unwrap(),
+            [
+                IndexValue::String(b"admin".to_vec()),
+                IndexValue::String(b"ro".to_vec())
+            ]
+        );
+        assert_eq!(decoder.decode(&arcs).unwrap()[0].as_str(), Some("admin"));
+
+        // Index strings can hold any octets, not just text.
+        let decoder = IndexDecoder::new(&[IndexSyntax::ImpliedString]);
+        let value = &decoder.decode(&[0x80, 0, 0xff]).unwrap()[0];
+        assert_eq!(value, &IndexValue::String(vec![0x80, 0, 0xff]));
+        assert_eq!(value.as_str(), None);
+
+        let decoder = IndexDecoder::new(&[IndexSyntax::String]);
+        assert!(decoder.decode(&[5, 97]).is_err());
+    }
+}
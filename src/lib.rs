@@ -1,10 +1,63 @@
+mod builder;
+mod cache;
 mod client;
+mod clock;
+mod codec;
+mod diff;
+mod dtls;
+mod error;
+mod host;
+mod index;
+mod mib;
+mod mib2;
 mod pdu;
+mod rate;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod table;
+mod target;
+#[cfg(test)]
+mod testing;
+mod transport;
+mod trap;
 mod types;
+mod usm;
+mod v3;
+mod walk;
 
 pub const SNMP_PORT: u16 = 161;
 
-pub use client::Client;
-pub use pdu::Message;
-pub use rasn::types::{ObjectIdentifier, OctetString};
-pub use types::{SnmpString, TimeTicks, Value, Version};
+pub use builder::ClientBuilder;
+pub use client::{Client, ReplySource, ResponseMeta, RetryPolicy, ValueTransform};
+#[cfg(feature = "testing")]
+pub use clock::MockClock;
+pub use clock::{Clock, SystemClock};
+pub use diff::{diff_bindings, Change};
+pub use dtls::{DtlsConfig, DtlsTransport};
+pub use error::{ErrorStatus, SnmpError};
+pub use host::StorageInfo;
+pub use index::{IndexDecoder, IndexSyntax, IndexValue};
+pub use mib::{MibRegistry, ObjectDef, Syntax};
+pub use mib2::{
+    enterprise_number, vendor_name, Capability, DeviceIdentity, IfStatus, IfType, InterfaceInfo,
+    SystemInfo,
+};
+pub use pdu::{DecodeLimits, Message, Pdu, PduTag};
+pub use rasn::types::{Class, ObjectIdentifier, OctetString};
+pub use rasn::Tag;
+pub use rate::RatePoller;
+pub use table::group_by_column;
+pub use target::{TargetConfig, Targets};
+pub use transport::Transport;
+pub use trap::{DuplicateFilter, NotificationKey, SNMP_TRAP_OID};
+pub use types::{
+    DateAndTime, DisplayStyle, ObjectIdentifierExt, SnmpString, TimeTicks, Value, ValueDisplay,
+    VarBinding, VarBindingDisplay, Version, MAX_OID_LEN,
+};
+pub use usm::{
+    key_change, localize_key, password_to_key, AuthOrPriv, AuthProtocol, ReportReason,
+    USM_STATS_DECRYPTION_ERRORS, USM_STATS_NOT_IN_TIME_WINDOWS, USM_STATS_UNKNOWN_ENGINE_IDS,
+    USM_STATS_UNKNOWN_USER_NAMES, USM_STATS_UNSUPPORTED_SEC_LEVELS, USM_STATS_WRONG_DIGESTS,
+};
+pub use v3::{EngineInfo, ScopedPdu, SecurityParameters, V3Message};
+pub use walk::{StopReason, WalkOutcome, WalkStrictness};
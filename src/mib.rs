@@ -0,0 +1,275 @@
+//! A registry of MIB object definitions, filled in from code, used to check
+//! values before they are sent.
+
+use crate::client::Client;
+use crate::error::SnmpError;
+use crate::transport::Transport;
+use crate::types::{ObjectIdentifier, Value, VarBinding};
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The SYNTAX of a MIB object.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Syntax {
+    /// An INTEGER, limited to the inclusive ranges given. No ranges means any
+    /// value.
+    Integer(Vec<(i32, i32)>),
+    /// An enumerated INTEGER with its named values.
+    Enumeration(Vec<(i32, String)>),
+    OctetString,
+    ObjectIdentifier,
+    IpAddress,
+    Counter32,
+    Gauge32,
+    TimeTicks,
+    Counter64,
+    Opaque,
+}
+
+impl Syntax {
+    /// Checks `value` against this syntax, describing the mismatch if any.
+    pub fn check(&self, value: &Value) -> Result<(), String> {
+        let matches = match (self, value) {
+            (Syntax::Integer(ranges), Value::Integer(v)) => {
+                if !ranges.is_empty() && !ranges.iter().any(|(lo, hi)| lo <= v && v <= hi) {
+                    return Err(format!("{} is outside {}", v, RangesDisplay(ranges)));
+                }
+                true
+            }
+            (Syntax::Enumeration(names), Value::Integer(v)) => {
+                if !names.iter().any(|(n, _)| n == v) {
+                    return Err(format!("{} is not one of {}", v, EnumDisplay(names)));
+                }
+                true
+            }
+            (Syntax::OctetString, Value::OctetStr(_))
+            | (Syntax::ObjectIdentifier, Value::Oid(_))
+            | (Syntax::IpAddress, Value::IpAddr(_))
+            | (Syntax::Counter32, Value::Counter32(_))
+            | (Syntax::Gauge32, Value::Gauge32(_))
+            | (Syntax::TimeTicks, Value::Timeticks(_))
+            | (Syntax::Counter64, Value::Counter64(_))
+            | (Syntax::Opaque, Value::Opaque(_)) => true,
+            _ => false,
+        };
+
+        if !matches {
+            return Err(format!("{:?} does not match syntax {:?}", value, self));
+        }
+        Ok(())
+    }
+}
+
+struct RangesDisplay<'a>(&'a [(i32, i32)]);
+
+impl<'a> fmt::Display for RangesDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (lo, hi)) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " | ")?;
+            }
+            write!(f, "{}..{}", lo, hi)?;
+        }
+        Ok(())
+    }
+}
+
+struct EnumDisplay<'a>(&'a [(i32, String)]);
+
+impl<'a> fmt::Display for EnumDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (n, name)) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}({})", name, n)?;
+        }
+        Ok(())
+    }
+}
+
+/// A MIB object definition.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectDef {
+    pub name: String,
+    pub syntax: Syntax,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MibRegistry {
+    objects: BTreeMap<Vec<u32>, ObjectDef>,
+}
+
+impl MibRegistry {
+    pub fn new() -> MibRegistry {
+        MibRegistry::default()
+    }
+
+    /// Registers the object type at `oid`, without an instance suffix.
+    pub fn insert(&mut self, oid: &[u32], name: &str, syntax: Syntax) {
+        let def = ObjectDef {
+            name: name.to_owned(),
+            syntax,
+        };
+        self.objects.insert(oid.to_vec(), def);
+    }
+
+    /// Finds the object that `oid`, usually an instance, belongs to: the
+    /// registered OID that is its longest prefix.
+    pub fn lookup(&self, oid: &ObjectIdentifier) -> Option<&ObjectDef> {
+        self.lookup_with_suffix(oid).map(|(def, _)| def)
+    }
+
+    /// Names `oid` symbolically, such as `sysName.0`, if its object is known.
+    pub fn name_of(&self, oid: &ObjectIdentifier) -> Option<String> {
+        let (def, suffix) = self.lookup_with_suffix(oid)?;
+
+        let mut name = def.name.clone();
+        for arc in suffix {
+            name.push_str(&format!(".{}", arc));
+        }
+        Some(name)
+    }
+
+    fn lookup_with_suffix<'o>(&self, oid: &'o ObjectIdentifier) -> Option<(&ObjectDef, &'o [u32])> {
+        (0..=oid.len())
+            .rev()
+            .find_map(|len| self.objects.get(&oid[..len]).map(|def| (def, &oid[len..])))
+    }
+}
+
+impl VarBinding {
+    /// Like `to_pair`, but names the OID symbolically when `registry` knows
+    /// its object.
+    pub fn to_named_pair(&self, registry: &MibRegistry) -> (String, Value) {
+        match registry.name_of(&self.name) {
+            Some(name) => (name, self.value.clone()),
+            None => self.to_pair(),
+        }
+    }
+}
+
+impl<'a, T: Transport> Client<'a, T> {
+    /// Like `set`, but first checks every value against its definition in
+    /// `registry`, failing with `InvalidValue` before anything is sent.
+    /// Bindings for objects the registry does not know are sent unchecked.
+    pub fn set_checked(
+        &mut self,
+        registry: &MibRegistry,
+        bindings: &[VarBinding],
+    ) -> Result<Vec<VarBinding>, SnmpError> {
+        for var in bindings {
+            if let Some(def) = registry.lookup(&var.name) {
+                def.syntax
+                    .check(&var.value)
+                    .map_err(|reason| SnmpError::InvalidValue {
+                        oid: var.name.clone(),
+                        reason: format!("{}: {}", def.name, reason),
+                    })?;
+            }
+        }
+
+        self.set(bindings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oid;
+    use crate::testing::*;
+    use crate::types::Version;
+
+    use std::sync::mpsc;
+
+    fn registry() -> MibRegistry {
+        let mut registry = MibRegistry::new();
+        registry.insert(
+            &[1, 3, 6, 1, 2, 1, 2, 2, 1, 7],
+            "ifAdminStatus",
+            Syntax::Enumeration(vec![
+                (1, "up".to_owned()),
+                (2, "down".to_owned()),
+                (3, "testing".to_owned()),
+            ]),
+        );
+        registry.insert(
+            &[1, 3, 6, 1, 4, 1, 99, 1],
+            "examplePort",
+            Syntax::Integer(vec![(1, 65535)]),
+        );
+        registry.insert(&[1, 3, 6, 1, 2, 1, 1, 5], "sysName", Syntax::OctetString);
+        registry
+    }
+
+    #[test]
+    fn syntax_check() {
+        let port = Syntax::Integer(vec![(1, 65535)]);
+        assert!(port.check(&Value::Integer(161)).is_ok());
+        assert_eq!(
+            port.check(&Value::Integer(70000)),
+            Err("70000 is outside 1..65535".to_owned())
+        );
+        assert!(port.check(&Value::Gauge32(161)).is_err());
+        assert!(Syntax::Integer(vec![]).check(&Value::Integer(-5)).is_ok());
+    }
+
+    #[test]
+    fn set_checked_rejects_before_sending() {
+        let (tx, rx) = mpsc::channel();
+        let mut socket = agent(move |request| {
+            tx.send(()).unwrap();
+            response(request, request.data().bindings())
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+        let registry = registry();
+
+        let r = c.set_checked(
+            &registry,
+            &[VarBinding::new(
+                oid! {1,3,6,1,2,1,2,2,1,7,3},
+                Value::Integer(9),
+            )],
+        );
+        match r {
+            Err(SnmpError::InvalidValue { oid, reason }) => {
+                assert_eq!(oid, oid! {1,3,6,1,2,1,2,2,1,7,3});
+                assert_eq!(
+                    reason,
+                    "ifAdminStatus: 9 is not one of up(1), down(2), testing(3)"
+                );
+            }
+            r => panic!("Unexpected result {:?}", r.map(|_| ())),
+        }
+        assert!(rx.try_recv().is_err());
+
+        c.set_checked(
+            &registry,
+            &[
+                VarBinding::new(oid! {1,3,6,1,2,1,2,2,1,7,3}, Value::Integer(2)),
+                VarBinding::new(oid! {1,3,6,1,2,1,1,5,0}, Value::from("core-1")),
+                VarBinding::new(oid! {1,3,6,1,4,1,100,1,0}, Value::Integer(-1)),
+            ],
+        )
+        .unwrap();
+        assert!(rx.try_recv().is_ok());
+    }
+
+    #[test]
+    fn named_pair() {
+        let registry = registry();
+
+        let vb = VarBinding::new(oid! {1,3,6,1,2,1,1,5,0}, Value::from("core-1"));
+        assert_eq!(
+            vb.to_named_pair(&registry),
+            ("sysName.0".to_owned(), Value::from("core-1"))
+        );
+
+        let vb = VarBinding::new(oid! {1,3,6,1,2,1,1,6,0}, Value::from("lab"));
+        assert_eq!(
+            vb.to_named_pair(&registry),
+            ("1.3.6.1.2.1.1.6.0".to_owned(), Value::from("lab"))
+        );
+    }
+}
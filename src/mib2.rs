@@ -0,0 +1,919 @@
+//! Typed helpers for the standard MIB-II groups (RFC 1213).
+
+use crate::client::Client;
+use crate::error::{ErrorStatus, SnmpError};
+use crate::table::column_roots;
+use crate::transport::Transport;
+use crate::types::{ObjectIdentifier, ObjectIdentifierExt, TimeTicks, Value, VarBinding, Version};
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+const SYS_DESCR: &[u32] = &[1, 3, 6, 1, 2, 1, 1, 1, 0];
+const SYS_OBJECT_ID: &[u32] = &[1, 3, 6, 1, 2, 1, 1, 2, 0];
+pub(crate) const SYS_UPTIME: &[u32] = &[1, 3, 6, 1, 2, 1, 1, 3, 0];
+const SYS_CONTACT: &[u32] = &[1, 3, 6, 1, 2, 1, 1, 4, 0];
+const SYS_NAME: &[u32] = &[1, 3, 6, 1, 2, 1, 1, 5, 0];
+const SYS_LOCATION: &[u32] = &[1, 3, 6, 1, 2, 1, 1, 6, 0];
+const SYS_SERVICES: &[u32] = &[1, 3, 6, 1, 2, 1, 1, 7, 0];
+
+/// The longest `ping` and the other probes wait for a response.
+const PING_TIMEOUT: Duration = Duration::from_secs(1);
+
+const SYSTEM_SCALARS: &[&[u32]] = &[
+    SYS_DESCR,
+    SYS_OBJECT_ID,
+    SYS_UPTIME,
+    SYS_CONTACT,
+    SYS_NAME,
+    SYS_LOCATION,
+    SYS_SERVICES,
+];
+
+const SYS_OR_ENTRY: &[u32] = &[1, 3, 6, 1, 2, 1, 1, 9, 1];
+const SYS_OR_ID: u32 = 2;
+const SYS_OR_DESCR: u32 = 3;
+const SYS_OR_UPTIME: u32 = 4;
+
+const IF_ENTRY: &[u32] = &[1, 3, 6, 1, 2, 1, 2, 2, 1];
+const IF_DESCR: u32 = 2;
+const IF_TYPE: u32 = 3;
+const IF_MTU: u32 = 4;
+const IF_SPEED: u32 = 5;
+const IF_PHYS_ADDRESS: u32 = 6;
+const IF_ADMIN_STATUS: u32 = 7;
+const IF_OPER_STATUS: u32 = 8;
+
+const IF_COLUMNS: &[u32] = &[
+    IF_DESCR,
+    IF_TYPE,
+    IF_MTU,
+    IF_SPEED,
+    IF_PHYS_ADDRESS,
+    IF_ADMIN_STATUS,
+    IF_OPER_STATUS,
+];
+
+/// The scalars of the `system` group. Objects the agent does not implement,
+/// or returns with an unexpected type, are `None`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SystemInfo {
+    pub descr: Option<String>,
+    pub object_id: Option<ObjectIdentifier>,
+    pub uptime: Option<TimeTicks>,
+    pub contact: Option<String>,
+    pub name: Option<String>,
+    pub location: Option<String>,
+    pub services: Option<i32>,
+}
+
+/// What identifies a device on first contact: its `system` group and the
+/// vendor its `sysObjectID` belongs to.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DeviceIdentity {
+    pub descr: Option<String>,
+    pub object_id: Option<ObjectIdentifier>,
+    pub uptime: Option<TimeTicks>,
+    pub name: Option<String>,
+    pub location: Option<String>,
+    pub contact: Option<String>,
+    pub enterprise: Option<u32>,
+    pub vendor: Option<String>,
+}
+
+const ENTERPRISES: &[u32] = &[1, 3, 6, 1, 4, 1];
+
+/// Private Enterprise Numbers of common network equipment vendors.
+const VENDORS: &[(u32, &str)] = &[
+    (2, "IBM"),
+    (9, "Cisco"),
+    (11, "HP"),
+    (171, "D-Link"),
+    (311, "Microsoft"),
+    (674, "Dell"),
+    (1916, "Extreme Networks"),
+    (2011, "Huawei"),
+    (2636, "Juniper"),
+    (3375, "F5"),
+    (4526, "Netgear"),
+    (6486, "Alcatel-Lucent"),
+    (6876, "VMware"),
+    (8072, "Net-SNMP"),
+    (11863, "TP-Link"),
+    (12356, "Fortinet"),
+    (14988, "MikroTik"),
+    (25461, "Palo Alto Networks"),
+    (30065, "Arista"),
+    (41112, "Ubiquiti"),
+];
+
+/// The Private Enterprise Number of an OID under `enterprises`
+/// (1.3.6.1.4.1), such as a `sysObjectID`.
+pub fn enterprise_number(oid: &ObjectIdentifier) -> Option<u32> {
+    let enterprises = ObjectIdentifier::new(ENTERPRISES.to_vec()).unwrap();
+    oid.strip_prefix(&enterprises)?.first().copied()
+}
+
+/// The vendor owning an enterprise OID. Vendors not in the built-in list are
+/// named by their enterprise number.
+pub fn vendor_name(oid: &ObjectIdentifier) -> Option<String> {
+    let pen = enterprise_number(oid)?;
+    let name = match VENDORS.iter().find(|(number, _)| *number == pen) {
+        Some((_, name)) => (*name).to_owned(),
+        None => pen.to_string(),
+    };
+
+    Some(name)
+}
+
+/// Interface types from the IANAifType-MIB. Values without a variant, and
+/// `other(1)` itself, are kept as `Other`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IfType {
+    EthernetCsmacd,
+    Ppp,
+    SoftwareLoopback,
+    PropVirtual,
+    Ieee80211,
+    Tunnel,
+    L2Vlan,
+    L3IpVlan,
+    Ieee8023adLag,
+    Mpls,
+    Bridge,
+    Other(i32),
+}
+
+impl SystemInfo {
+    /// The vendor named by `object_id`; see `vendor_name`.
+    pub fn vendor(&self) -> Option<String> {
+        self.object_id.as_ref().and_then(vendor_name)
+    }
+}
+
+impl IfType {
+    pub fn from_i32(value: i32) -> IfType {
+        match value {
+            6 => IfType::EthernetCsmacd,
+            23 => IfType::Ppp,
+            24 => IfType::SoftwareLoopback,
+            53 => IfType::PropVirtual,
+            71 => IfType::Ieee80211,
+            131 => IfType::Tunnel,
+            135 => IfType::L2Vlan,
+            136 => IfType::L3IpVlan,
+            161 => IfType::Ieee8023adLag,
+            166 => IfType::Mpls,
+            209 => IfType::Bridge,
+            _ => IfType::Other(value),
+        }
+    }
+}
+
+impl fmt::Display for IfType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            IfType::EthernetCsmacd => "ethernetCsmacd",
+            IfType::Ppp => "ppp",
+            IfType::SoftwareLoopback => "softwareLoopback",
+            IfType::PropVirtual => "propVirtual",
+            IfType::Ieee80211 => "ieee80211",
+            IfType::Tunnel => "tunnel",
+            IfType::L2Vlan => "l2vlan",
+            IfType::L3IpVlan => "l3ipvlan",
+            IfType::Ieee8023adLag => "ieee8023adLag",
+            IfType::Mpls => "mpls",
+            IfType::Bridge => "bridge",
+            IfType::Other(1) => "other",
+            IfType::Other(value) => return write!(f, "unknown({})", value),
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// A row of `sysORTable`: a MIB module or capability the agent implements.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Capability {
+    pub index: u32,
+    pub id: ObjectIdentifier,
+    pub descr: String,
+    /// `sysUpTime` when the row was last instantiated.
+    pub uptime: TimeTicks,
+}
+
+/// The values of `ifAdminStatus` and `ifOperStatus`. An admin status is only
+/// ever `Up`, `Down` or `Testing`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IfStatus {
+    Up,
+    Down,
+    Testing,
+    Unknown,
+    Dormant,
+    NotPresent,
+    LowerLayerDown,
+    /// A value the MIB does not define, unlike its own `unknown(4)`.
+    Other(i32),
+}
+
+impl IfStatus {
+    pub fn from_i32(value: i32) -> IfStatus {
+        match value {
+            1 => IfStatus::Up,
+            2 => IfStatus::Down,
+            3 => IfStatus::Testing,
+            4 => IfStatus::Unknown,
+            5 => IfStatus::Dormant,
+            6 => IfStatus::NotPresent,
+            7 => IfStatus::LowerLayerDown,
+            _ => IfStatus::Other(value),
+        }
+    }
+}
+
+impl fmt::Display for IfStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            IfStatus::Up => "up",
+            IfStatus::Down => "down",
+            IfStatus::Testing => "testing",
+            IfStatus::Unknown => "unknown",
+            IfStatus::Dormant => "dormant",
+            IfStatus::NotPresent => "notPresent",
+            IfStatus::LowerLayerDown => "lowerLayerDown",
+            IfStatus::Other(value) => return write!(f, "unknown({})", value),
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// A row of `ifTable`. Columns the agent does not return are `None`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InterfaceInfo {
+    pub index: u32,
+    pub descr: Option<String>,
+    pub if_type: Option<IfType>,
+    pub mtu: Option<i32>,
+    pub speed: Option<u32>,
+    pub phys_address: Option<Vec<u8>>,
+    pub admin_status: Option<IfStatus>,
+    pub oper_status: Option<IfStatus>,
+}
+
+impl<'a, T: Transport> Client<'a, T> {
+    /// Checks whether an SNMP agent answers, by GETting `sysUpTime.0`. Any
+    /// response counts, even one with an error-status. A timeout or an
+    /// unreachable port is `Ok(false)`, as is a reply from an unexpected
+    /// source; other failures are returned.
+    ///
+    /// The request is sent once, without retries, and waits at most one
+    /// second for the response.
+    pub fn ping(&mut self) -> Result<bool, SnmpError> {
+        let r = self.with_probe_timeout(|c| {
+            c.get_distinct(&[ObjectIdentifier::new(SYS_UPTIME.to_vec()).unwrap()])
+        });
+        answered(r)
+    }
+
+    /// Probes which versions the agent answers: SNMPv1 and SNMPv2c with a GET
+    /// of `sysDescr.0` in the client's communities, SNMPv3 with engine
+    /// discovery. Versions are tried in order and the client is left on the
+    /// version it was using. Versions a `v3_only` client refuses to send are
+    /// not supported.
+    ///
+    /// Each request is sent once and waits at most one second for the
+    /// response.
+    pub fn supported_versions(&mut self) -> Result<Vec<Version>, SnmpError> {
+        let current = self.version();
+        let mut supported = vec![];
+        for version in [Version::V1, Version::V2C, Version::V3].iter() {
+            self.set_version(*version);
+            let r = self.with_probe_timeout(|c| match c.version() {
+                Version::V3 => c.discover_engine().map(|_| ()),
+                _ => c
+                    .get_distinct(&[ObjectIdentifier::new(SYS_DESCR.to_vec()).unwrap()])
+                    .map(|_| ()),
+            });
+
+            let r = match r {
+                Err(SnmpError::CommunityDisabled(_)) => Ok(false),
+                r => answered(r),
+            };
+            match r {
+                Ok(true) => supported.push(*version),
+                Ok(false) => {}
+                Err(e) => {
+                    self.set_version(current);
+                    return Err(e);
+                }
+            }
+        }
+
+        self.set_version(current);
+        Ok(supported)
+    }
+
+    /// Finds which of `candidates` the agent accepts as read community, by
+    /// GETting `sysDescr.0` in each in turn until one is answered. Agents drop
+    /// requests in a community they do not know, so each wrong candidate
+    /// costs a timeout; the rate limit applies as well. The client is left on
+    /// its own read community.
+    ///
+    /// Only probe agents you are authorized to audit: every wrong candidate
+    /// is logged as an authentication failure on the agent, and may trigger
+    /// an `authenticationFailure` trap.
+    ///
+    /// Each request is sent once and waits at most one second for the
+    /// response. SNMPv3 has no communities, so a client on `Version::V3`
+    /// fails with `Unsupported` without sending anything.
+    pub fn find_community(&mut self, candidates: &[&str]) -> Result<Option<String>, SnmpError> {
+        if self.version() == Version::V3 {
+            return Err(SnmpError::Unsupported("community strings in SNMPv3"));
+        }
+        let current = match self.read_community() {
+            Some(community) => community.to_owned(),
+            None => return Err(SnmpError::CommunityDisabled(self.version())),
+        };
+
+        let sys_descr = ObjectIdentifier::new(SYS_DESCR.to_vec()).unwrap();
+        let mut found = Ok(None);
+        for candidate in candidates {
+            self.set_read_community(candidate);
+            // Bypasses the cache, which would answer for another community.
+            let r = self.with_probe_timeout(|c| c.get_distinct(std::slice::from_ref(&sys_descr)));
+            match answered(r) {
+                Ok(false) => continue,
+                Ok(true) => found = Ok(Some(candidate.to_string())),
+                Err(e) => found = Err(e),
+            }
+            break;
+        }
+
+        self.set_read_community(&current);
+        found
+    }
+
+    /// Runs `probe` with a single attempt waiting no longer than
+    /// `PING_TIMEOUT`, then restores the client's timeout and retries.
+    fn with_probe_timeout<R>(&mut self, probe: impl FnOnce(&mut Self) -> R) -> R {
+        let policy = self.retry_policy();
+        let retries = self.retries();
+        let wait = policy.map_or(PING_TIMEOUT, |p| p.delay(0).min(PING_TIMEOUT));
+        self.set_timeout(Some(wait));
+        self.set_retries(0);
+
+        let r = probe(self);
+
+        match policy {
+            Some(policy) => self.set_retry_policy(policy),
+            None => self.set_timeout(None),
+        }
+        self.set_retries(retries);
+        r
+    }
+
+    /// Fetches the `system` group in a single GET.
+    pub fn system(&mut self) -> Result<SystemInfo, SnmpError> {
+        let oids = SYSTEM_SCALARS
+            .iter()
+            .map(|oid| ObjectIdentifier::new(oid.to_vec()).unwrap())
+            .collect();
+
+        let mut info = SystemInfo::default();
+        for var in get_available(self, oids)? {
+            let value = &var.value;
+            match var.name.as_slice() {
+                SYS_DESCR => info.descr = value.as_string(),
+                SYS_OBJECT_ID => info.object_id = value.as_oid().cloned(),
+                SYS_UPTIME => info.uptime = value.as_timeticks().cloned(),
+                SYS_CONTACT => info.contact = value.as_string(),
+                SYS_NAME => info.name = value.as_string(),
+                SYS_LOCATION => info.location = value.as_string(),
+                SYS_SERVICES => info.services = value.as_i32(),
+                _ => {}
+            }
+        }
+
+        Ok(info)
+    }
+
+    /// Identifies the device from its `system` group, fetched in a single GET.
+    pub fn discover(&mut self) -> Result<DeviceIdentity, SnmpError> {
+        let info = self.system()?;
+
+        Ok(DeviceIdentity {
+            enterprise: info.object_id.as_ref().and_then(enterprise_number),
+            vendor: info.vendor(),
+            descr: info.descr,
+            object_id: info.object_id,
+            uptime: info.uptime,
+            name: info.name,
+            location: info.location,
+            contact: info.contact,
+        })
+    }
+
+    /// Walks `sysORTable`, returning the agent's capabilities in `sysORIndex`
+    /// order. Rows without a `sysORID` are left out; a missing description or
+    /// uptime is empty or zero.
+    pub fn capabilities(&mut self) -> Result<Vec<Capability>, SnmpError> {
+        static ROOTS: OnceLock<Vec<ObjectIdentifier>> = OnceLock::new();
+        let columns = [SYS_OR_ID, SYS_OR_DESCR, SYS_OR_UPTIME];
+        let roots = column_roots(&ROOTS, SYS_OR_ENTRY, &columns);
+
+        let mut rows = BTreeMap::new();
+        for (column, vars) in columns.iter().zip(self.walk_many(roots)?) {
+            for var in vars {
+                let index = match var.name.as_slice().strip_prefix(SYS_OR_ENTRY) {
+                    Some([_, index]) => *index,
+                    _ => continue,
+                };
+                let row: &mut (Option<ObjectIdentifier>, Option<String>, Option<TimeTicks>) =
+                    rows.entry(index).or_default();
+
+                let value = &var.value;
+                match *column {
+                    SYS_OR_ID => row.0 = value.as_oid().cloned(),
+                    SYS_OR_DESCR => row.1 = value.as_string(),
+                    SYS_OR_UPTIME => row.2 = value.as_timeticks().cloned(),
+                    _ => {}
+                }
+            }
+        }
+
+        let capabilities = rows
+            .into_iter()
+            .filter_map(|(index, (id, descr, uptime))| {
+                Some(Capability {
+                    index,
+                    id: id?,
+                    descr: descr.unwrap_or_default(),
+                    uptime: uptime.unwrap_or_else(|| TimeTicks::new(0)),
+                })
+            })
+            .collect();
+        Ok(capabilities)
+    }
+
+    /// Walks `ifTable`, returning one entry per interface in `ifIndex` order.
+    pub fn interfaces(&mut self) -> Result<Vec<InterfaceInfo>, SnmpError> {
+        static ROOTS: OnceLock<Vec<ObjectIdentifier>> = OnceLock::new();
+        let roots = column_roots(&ROOTS, IF_ENTRY, IF_COLUMNS);
+
+        let mut rows = BTreeMap::new();
+        for (column, vars) in IF_COLUMNS.iter().zip(self.walk_many(roots)?) {
+            for var in vars {
+                let index = match var.name.as_slice().strip_prefix(IF_ENTRY) {
+                    Some([_, index]) => *index,
+                    _ => continue,
+                };
+                let row = rows.entry(index).or_insert_with(|| InterfaceInfo {
+                    index,
+                    ..InterfaceInfo::default()
+                });
+
+                let value = &var.value;
+                match *column {
+                    IF_DESCR => row.descr = value.as_string(),
+                    IF_TYPE => row.if_type = value.as_i32().map(IfType::from_i32),
+                    IF_MTU => row.mtu = value.as_i32(),
+                    IF_SPEED => {
+                        if let Value::Gauge32(v) = value {
+                            row.speed = Some(*v)
+                        }
+                    }
+                    IF_PHYS_ADDRESS => row.phys_address = value.as_bytes().map(|v| v.to_vec()),
+                    IF_ADMIN_STATUS => row.admin_status = value.as_i32().map(IfStatus::from_i32),
+                    IF_OPER_STATUS => row.oper_status = value.as_i32().map(IfStatus::from_i32),
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(rows.into_values().collect())
+    }
+}
+
+/// GETs `oids`, leaving out the ones an SNMPv1 agent rejects with `noSuchName`
+/// until the rest succeed. v2c agents report missing objects as exception
+/// values instead, which are left in the result.
+fn get_available<T: Transport>(
+    client: &mut Client<T>,
+    mut oids: Vec<ObjectIdentifier>,
+) -> Result<Vec<VarBinding>, SnmpError> {
+    while !oids.is_empty() {
+        match client.get(&oids) {
+            Err(e) => match rejected(&e, &oids) {
+                Some(i) => {
+                    oids.remove(i);
+                }
+                None => return Err(e),
+            },
+            r => return r,
+        }
+    }
+
+    Ok(vec![])
+}
+
+/// Where in `oids` the object an SNMPv1 `noSuchName` rejects is. The error's
+/// `oid` is looked up where there is one: its `index` counts in the request
+/// actually sent, which after a `tooBig` split is only part of `oids`.
+fn rejected(e: &SnmpError, oids: &[ObjectIdentifier]) -> Option<usize> {
+    match e {
+        SnmpError::Agent {
+            status: ErrorStatus::NoSuchName,
+            oid: Some(oid),
+            ..
+        } => oids.iter().position(|o| o == oid),
+        SnmpError::Agent {
+            status: ErrorStatus::NoSuchName,
+            index,
+            oid: None,
+        } if *index >= 1 && *index as usize <= oids.len() => Some(*index as usize - 1),
+        _ => None,
+    }
+}
+
+/// Whether a probe got any response: an error-status counts, a timeout or an
+/// unreachable port does not.
+fn answered<R>(r: Result<R, SnmpError>) -> Result<bool, SnmpError> {
+    match r {
+        Ok(_) | Err(SnmpError::Agent { .. }) => Ok(true),
+        Err(SnmpError::Timeout)
+        | Err(SnmpError::PortUnreachable)
+        | Err(SnmpError::UnexpectedSource(_)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::RetryPolicy;
+    use crate::oid;
+    use crate::pdu::{Message, Pdu, PduTag};
+    use crate::testing::*;
+    use crate::v3::{ScopedPdu, SecurityParameters, V3Message};
+
+    use rasn::types::OctetString;
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::mpsc;
+
+    fn system_mib() -> Vec<VarBinding> {
+        vec![
+            VarBinding::new(
+                oid! {1,3,6,1,2,1,1,1,0},
+                Value::OctetStr(OctetString::from_static(b"Linux router")),
+            ),
+            VarBinding::new(
+                oid! {1,3,6,1,2,1,1,2,0},
+                Value::Oid(oid! {1,3,6,1,4,1,8072,3,2,10}),
+            ),
+            VarBinding::new(
+                oid! {1,3,6,1,2,1,1,3,0},
+                Value::Timeticks(TimeTicks::new(12345)),
+            ),
+            VarBinding::new(
+                oid! {1,3,6,1,2,1,1,5,0},
+                Value::OctetStr(OctetString::from_static(b"core-1")),
+            ),
+            VarBinding::new(oid! {1,3,6,1,2,1,1,7,0}, Value::Integer(72)),
+        ]
+    }
+
+    /// Answers GETs in the community-based `versions`, and engine discovery
+    /// too if `v3` is set. Everything else is dropped.
+    fn versioned_agent(versions: &'static [Version], v3: bool) -> MockTransport {
+        let mut lookup = get_agent(system_mib());
+        MockTransport::new(move |buf| {
+            if let Ok(request) = Message::from_bytes(buf) {
+                if versions.contains(&request.version()) {
+                    return vec![lookup(&request).to_bytes().unwrap()];
+                }
+            } else if let Ok(request) = V3Message::from_bytes(buf) {
+                if v3 {
+                    let security = SecurityParameters {
+                        engine_id: vec![0x80, 0, 0x1f, 0x88, 4],
+                        ..SecurityParameters::default()
+                    };
+                    let pdu = Pdu::new(PduTag::Report, request.scoped_pdu().data().request_id());
+                    let scoped_pdu = ScopedPdu::new(&security.engine_id, "", pdu);
+                    let report = V3Message::new(request.msg_id(), security, scoped_pdu);
+                    return vec![report.to_bytes().unwrap()];
+                }
+            }
+            vec![]
+        })
+    }
+
+    #[test]
+    fn supported_versions() {
+        let mut transport = versioned_agent(&[Version::V2C], false);
+        let mut c = Client::new(Version::V1, &mut transport);
+        assert_eq!(c.supported_versions().unwrap(), [Version::V2C]);
+        assert_eq!(c.version(), Version::V1);
+
+        let mut transport = versioned_agent(&[Version::V1], true);
+        let mut c = Client::new(Version::V2C, &mut transport);
+        assert_eq!(c.supported_versions().unwrap(), [Version::V1, Version::V3]);
+        assert_eq!(c.version(), Version::V2C);
+        assert!(c.engine().is_some());
+
+        let mut transport = versioned_agent(&[Version::V1, Version::V2C], true);
+        let mut c = Client::new(Version::V3, &mut transport).v3_only();
+        assert_eq!(c.supported_versions().unwrap(), [Version::V3]);
+    }
+
+    #[test]
+    fn find_community_stops_at_first_answer() {
+        let tried = Rc::new(RefCell::new(vec![]));
+        let seen = tried.clone();
+        let mut lookup = get_agent(system_mib());
+        let mut transport = MockTransport::agent(move |request| {
+            let community = request.community().into_owned();
+            seen.borrow_mut().push(community.clone());
+            match community.as_str() {
+                "s3cret" => vec![lookup(request)],
+                _ => vec![],
+            }
+        });
+        let mut c = Client::new(Version::V2C, &mut transport);
+
+        let candidates = ["public", "private", "s3cret", "admin"];
+        let found = c.find_community(&candidates).unwrap();
+        assert_eq!(found.as_deref(), Some("s3cret"));
+        assert_eq!(*tried.borrow(), candidates[..3]);
+
+        assert_eq!(c.find_community(&["admin"]).unwrap(), None);
+        let r = c.get(&[oid! {1,3,6,1,2,1,1,1,0}]);
+        assert!(matches!(r, Err(SnmpError::Timeout)));
+        assert_eq!(tried.borrow().last().unwrap(), "public");
+    }
+
+    #[test]
+    fn find_community_needs_a_community_version() {
+        let mut lookup = get_agent(system_mib());
+        let mut transport = MockTransport::agent(move |request| vec![lookup(request)]);
+        let mut c = Client::new(Version::V3, &mut transport);
+        let r = c.find_community(&["public"]);
+        assert!(matches!(r, Err(SnmpError::Unsupported(_))));
+
+        let mut c = Client::new(Version::V2C, &mut transport).v3_only();
+        let r = c.find_community(&["public"]);
+        assert!(matches!(r, Err(SnmpError::CommunityDisabled(Version::V2C))));
+        assert!(transport.sent.is_empty());
+    }
+
+    #[test]
+    fn system_info() {
+        for version in [Version::V1, Version::V2C].iter() {
+            let mut socket = agent(get_agent(system_mib()));
+            let mut c = Client::new(*version, &mut socket);
+
+            let info = c.system().unwrap();
+            assert_eq!(
+                info,
+                SystemInfo {
+                    descr: Some("Linux router".to_owned()),
+                    object_id: Some(oid! {1,3,6,1,4,1,8072,3,2,10}),
+                    uptime: Some(TimeTicks::new(12345)),
+                    contact: None,
+                    name: Some("core-1".to_owned()),
+                    location: None,
+                    services: Some(72),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn system_info_v1_after_too_big_split() {
+        let mut lookup = get_agent(system_mib());
+        let mut socket = agent(move |request| {
+            if request.data().bindings().len() > 4 {
+                let pdu = Pdu::new(PduTag::GetResponse, request.data().request_id())
+                    .with_error(1, 0)
+                    .with_bindings(request.data().bindings());
+                return Message::with_raw_community(
+                    request.version(),
+                    request.community_bytes(),
+                    pdu,
+                );
+            }
+            lookup(request)
+        });
+        let mut c = Client::new(Version::V1, &mut socket);
+
+        // sysContact.0 is rejected at index 1 of the second half, which is
+        // not sysDescr.0.
+        let info = c.system().unwrap();
+        assert_eq!(info.descr.as_deref(), Some("Linux router"));
+        assert_eq!(info.contact, None);
+        assert_eq!(info.location, None);
+        assert_eq!(info.services, Some(72));
+    }
+
+    #[test]
+    fn discover() {
+        let (tx, rx) = mpsc::channel();
+        let mut lookup = get_agent(system_mib());
+        let mut socket = agent(move |request| {
+            tx.send(()).unwrap();
+            lookup(request)
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        let identity = c.discover().unwrap();
+        assert_eq!(
+            identity,
+            DeviceIdentity {
+                descr: Some("Linux router".to_owned()),
+                object_id: Some(oid! {1,3,6,1,4,1,8072,3,2,10}),
+                uptime: Some(TimeTicks::new(12345)),
+                name: Some("core-1".to_owned()),
+                location: None,
+                contact: None,
+                enterprise: Some(8072),
+                vendor: Some("Net-SNMP".to_owned()),
+            }
+        );
+        assert_eq!(rx.try_iter().count(), 1);
+    }
+
+    #[test]
+    fn ping() {
+        let mut socket = agent(get_agent(system_mib()));
+        let mut c = Client::new(Version::V2C, &mut socket);
+        assert!(c.ping().unwrap());
+        assert_eq!(c.retry_policy(), None);
+
+        // v1 agents answer a missing object with an error, which still counts.
+        let mut socket = agent(get_agent(vec![]));
+        let mut c = Client::new(Version::V1, &mut socket);
+        assert!(c.ping().unwrap());
+
+        let mut socket = raw_agent(|_| vec![]);
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_timeout(Some(Duration::from_millis(50)));
+        assert!(!c.ping().unwrap());
+    }
+
+    #[test]
+    fn ping_sends_once_and_restores_retries() {
+        let mut transport = MockTransport::new(|_| vec![]);
+        {
+            let mut c = Client::new(Version::V2C, &mut transport);
+            c.set_timeout(Some(Duration::from_secs(30)));
+            c.set_retries(3);
+            assert!(!c.ping().unwrap());
+            assert_eq!(
+                c.retry_policy(),
+                Some(RetryPolicy::Fixed(Duration::from_secs(30)))
+            );
+            assert_eq!(c.retries(), 3);
+        }
+        assert_eq!(transport.sent.len(), 1);
+
+        let stray = "127.0.0.1:1161".parse().unwrap();
+        assert!(!answered::<()>(Err(SnmpError::UnexpectedSource(stray))).unwrap());
+    }
+
+    #[test]
+    fn if_type_codes() {
+        assert_eq!(IfType::from_i32(6), IfType::EthernetCsmacd);
+        assert_eq!(IfType::from_i32(24), IfType::SoftwareLoopback);
+        assert_eq!(IfType::from_i32(131), IfType::Tunnel);
+        assert_eq!(IfType::from_i32(9999), IfType::Other(9999));
+
+        assert_eq!(IfType::EthernetCsmacd.to_string(), "ethernetCsmacd");
+        assert_eq!(IfType::Other(1).to_string(), "other");
+        assert_eq!(IfType::Other(9999).to_string(), "unknown(9999)");
+    }
+
+    #[test]
+    fn if_status_codes() {
+        assert_eq!(IfStatus::from_i32(1), IfStatus::Up);
+        assert_eq!(IfStatus::from_i32(2), IfStatus::Down);
+        assert_eq!(IfStatus::from_i32(3), IfStatus::Testing);
+        assert_eq!(IfStatus::from_i32(5), IfStatus::Dormant);
+        assert_eq!(IfStatus::from_i32(7), IfStatus::LowerLayerDown);
+        assert_eq!(IfStatus::from_i32(0), IfStatus::Other(0));
+
+        assert_eq!(IfStatus::Up.to_string(), "up");
+        assert_eq!(IfStatus::Down.to_string(), "down");
+        assert_eq!(IfStatus::Unknown.to_string(), "unknown");
+        assert_eq!(IfStatus::NotPresent.to_string(), "notPresent");
+        assert_eq!(IfStatus::LowerLayerDown.to_string(), "lowerLayerDown");
+        assert_eq!(IfStatus::Other(9).to_string(), "unknown(9)");
+    }
+
+    #[test]
+    fn interfaces() {
+        let column = |c: u32, i: u32| oid! {1,3,6,1,2,1,2,2,1}.child(&[c, i]);
+        let mib = vec![
+            VarBinding::new(oid! {1,3,6,1,2,1,2,1,0}, Value::Integer(2)),
+            VarBinding::new(column(2, 1), Value::from("lo")),
+            VarBinding::new(column(2, 2), Value::from("eth0")),
+            VarBinding::new(column(3, 1), Value::Integer(24)),
+            VarBinding::new(column(3, 2), Value::Integer(6)),
+            VarBinding::new(column(5, 2), Value::Gauge32(1_000_000_000)),
+            VarBinding::new(column(8, 1), Value::Integer(1)),
+            VarBinding::new(column(8, 2), Value::Integer(2)),
+            VarBinding::new(oid! {1,3,6,1,2,1,4,1,0}, Value::Integer(1)),
+        ];
+        let mut socket = agent(get_next_agent(mib));
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        let interfaces = c.interfaces().unwrap();
+        assert_eq!(
+            interfaces,
+            vec![
+                InterfaceInfo {
+                    index: 1,
+                    descr: Some("lo".to_owned()),
+                    if_type: Some(IfType::SoftwareLoopback),
+                    oper_status: Some(IfStatus::Up),
+                    ..InterfaceInfo::default()
+                },
+                InterfaceInfo {
+                    index: 2,
+                    descr: Some("eth0".to_owned()),
+                    if_type: Some(IfType::EthernetCsmacd),
+                    speed: Some(1_000_000_000),
+                    oper_status: Some(IfStatus::Down),
+                    ..InterfaceInfo::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn capabilities() {
+        let column = |c: u32, i: u32| oid! {1,3,6,1,2,1,1,9,1}.child(&[c, i]);
+        let mib = vec![
+            VarBinding::new(column(2, 1), Value::Oid(oid! {1,3,6,1,6,3,1})),
+            VarBinding::new(column(2, 2), Value::Oid(oid! {1,3,6,1,2,1,49})),
+            VarBinding::new(
+                column(3, 1),
+                Value::OctetStr(OctetString::from_static(b"The MIB for SNMPv2 entities")),
+            ),
+            VarBinding::new(
+                column(3, 2),
+                Value::OctetStr(OctetString::from_static(b"The MIB module for TCP")),
+            ),
+            VarBinding::new(column(4, 1), Value::Timeticks(TimeTicks::new(5))),
+            VarBinding::new(column(4, 2), Value::Timeticks(TimeTicks::new(7))),
+            VarBinding::new(oid! {1,3,6,1,2,1,2,1,0}, Value::Integer(2)),
+        ];
+        let mut socket = agent(get_next_agent(mib));
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        assert_eq!(
+            c.capabilities().unwrap(),
+            vec![
+                Capability {
+                    index: 1,
+                    id: oid! {1,3,6,1,6,3,1},
+                    descr: "The MIB for SNMPv2 entities".to_owned(),
+                    uptime: TimeTicks::new(5),
+                },
+                Capability {
+                    index: 2,
+                    id: oid! {1,3,6,1,2,1,49},
+                    descr: "The MIB module for TCP".to_owned(),
+                    uptime: TimeTicks::new(7),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn vendor_from_object_id() {
+        let catalyst = oid! {1,3,6,1,4,1,9,1,1208};
+        assert_eq!(enterprise_number(&catalyst), Some(9));
+        assert_eq!(vendor_name(&catalyst), Some("Cisco".to_owned()));
+
+        let mx = oid! {1,3,6,1,4,1,2636,1,1,1,2,21};
+        assert_eq!(vendor_name(&mx), Some("Juniper".to_owned()));
+        assert_eq!(
+            vendor_name(&oid! {1,3,6,1,4,1,99999,1}),
+            Some("99999".to_owned())
+        );
+
+        assert_eq!(enterprise_number(&oid! {1,3,6,1,4,1}), None);
+        assert_eq!(enterprise_number(&oid! {1,3,6,1,2,1,1,2,0}), None);
+
+        let info = SystemInfo {
+            object_id: Some(oid! {1,3,6,1,4,1,8072,3,2,10}),
+            ..SystemInfo::default()
+        };
+        assert_eq!(info.vendor(), Some("Net-SNMP".to_owned()));
+    }
+}
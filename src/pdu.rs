@@ -1,9 +1,16 @@
-use crate::types::{SnmpString, VarBinding, Version};
+use crate::codec::{Ber, Codec};
+use crate::error::{ErrorStatus, SnmpError};
+use crate::mib::MibRegistry;
+use crate::types::{DisplayStyle, ObjectIdentifierDisplayWrapper, Value, VarBinding, Version};
 
 use rasn::de::Error;
-use rasn::types::{Class, ObjectIdentifier};
+use rasn::types::{Class, ObjectIdentifier, OctetString};
 use rasn::{AsnType, Decode, Decoder, Encode, Encoder, Tag};
 
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::convert::TryFrom;
+
 const TAG_MSG_GET: Tag = Tag::new(Class::Context, 0);
 const TAG_MSG_GETNEXT: Tag = Tag::new(Class::Context, 1);
 const TAG_MSG_RESPONSE: Tag = Tag::new(Class::Context, 2);
@@ -17,7 +24,7 @@ const TAG_MSG_REPORT: Tag = Tag::new(Class::Context, 8);
 #[derive(Debug)]
 pub struct Message {
     version: Version,
-    community: String,
+    community: OctetString,
     data: Pdu,
 }
 
@@ -25,24 +32,314 @@ impl Message {
     pub fn new(version: Version, community: &str, data: Pdu) -> Self {
         Message {
             version,
-            community: community.to_owned(),
+            community: OctetString::copy_from_slice(community.as_bytes()),
             data,
         }
     }
 
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Like `new`, for communities that are not valid UTF-8.
+    pub fn with_raw_community(version: Version, community: &[u8], data: Pdu) -> Self {
+        Message {
+            version,
+            community: OctetString::copy_from_slice(community),
+            data,
+        }
+    }
+
+    /// The community as text, with invalid UTF-8 replaced.
+    pub fn community(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.community)
+    }
+
+    pub fn community_bytes(&self) -> &[u8] {
+        &self.community
+    }
+
     pub fn data(&self) -> &Pdu {
         &self.data
     }
+
+    /// Builds the GetResponse to this request, for an agent: the version,
+    /// community and request-id are echoed and `bindings` carried with no
+    /// error. Use `with_error` on a `Pdu` of its own to report one.
+    pub fn make_response(&self, bindings: &[VarBinding]) -> Message {
+        let pdu = Pdu::new(PduTag::GetResponse, self.data.request_id).with_bindings(bindings);
+
+        Message {
+            version: self.version,
+            community: self.community.clone(),
+            data: pdu,
+        }
+    }
+
+    /// Builds the acknowledgement for a received InformRequest: a GetResponse
+    /// echoing its version, community, request-id and bindings, with no error
+    /// (RFC 3416 4.2.7).
+    pub fn make_inform_response(&self) -> Message {
+        self.make_response(&self.data.bindings)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SnmpError> {
+        Ber::encode(self)
+    }
+
+    /// The size of the encoded message, e.g. to check a request fits the
+    /// agent's maximum message size before sending it.
+    pub fn encoded_len(&self) -> Result<usize, SnmpError> {
+        self.to_bytes().map(|b| b.len())
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Message, SnmpError> {
+        Message::from_bytes_with_limits(bytes, DecodeLimits::default())
+    }
+
+    /// Like `from_bytes`, but rejects the message with `MessageTooLarge` if it
+    /// exceeds `limits`. The declared length is checked before decoding.
+    ///
+    /// SNMPv1 Trap-PDUs are not decoded yet and fail with `Unsupported`.
+    pub fn from_bytes_with_limits(
+        bytes: &[u8],
+        limits: DecodeLimits,
+    ) -> Result<Message, SnmpError> {
+        limits.check_size(bytes)?;
+        if community_pdu_tag(bytes) == Some(TAG_MSG_TRAPV1) {
+            return Err(SnmpError::Unsupported("SNMPv1 Trap-PDUs"));
+        }
+        limits.decode(bytes)
+    }
+
+    /// Renders the message as an indented tree of its fields, the way packet
+    /// analysers show it, for logs and bug reports. The layout is meant for
+    /// people and may change.
+    pub fn debug_tree(&self) -> String {
+        self.tree(None)
+    }
+
+    /// Like `debug_tree`, but names OIDs symbolically when `registry` knows
+    /// their objects.
+    pub fn debug_tree_named(&self, registry: &MibRegistry) -> String {
+        self.tree(Some(registry))
+    }
+
+    fn tree(&self, registry: Option<&MibRegistry>) -> String {
+        let mut tree = String::new();
+        let mut line = |depth: usize, text: String| {
+            tree.push_str(&"  ".repeat(depth));
+            tree.push_str(&text);
+            tree.push('\n');
+        };
+
+        let pdu = &self.data;
+        line(0, format!("version: {}", self.version));
+        line(0, format!("community: {:?}", self.community()));
+        line(0, format!("data: {:?}", pdu.tag));
+        line(1, format!("request-id: {}", pdu.request_id));
+        if pdu.tag == PduTag::GetBulkRequest {
+            line(1, format!("non-repeaters: {}", pdu.err_status));
+            line(1, format!("max-repetitions: {}", pdu.err_index));
+        } else {
+            let status = match ErrorStatus::from_i32(pdu.err_status) {
+                ErrorStatus::Unknown(_) => format!("unknown ({})", pdu.err_status),
+                status => format!("{} ({})", status, pdu.err_status),
+            };
+            line(1, format!("error-status: {}", status));
+            line(1, format!("error-index: {}", pdu.err_index));
+        }
+
+        line(
+            1,
+            format!("variable-bindings: {} items", pdu.bindings.len()),
+        );
+        for vb in &pdu.bindings {
+            let oid = ObjectIdentifierDisplayWrapper(&vb.name);
+            match registry.and_then(|registry| registry.name_of(&vb.name)) {
+                Some(name) => line(2, format!("{} ({})", name, oid)),
+                None => line(2, oid.to_string()),
+            }
+            line(3, vb.value.display(DisplayStyle::NetSnmp).to_string());
+        }
+
+        tree
+    }
+}
+
+/// Bounds on what `Message::from_bytes_with_limits` accepts, so a crafted
+/// datagram cannot make a receiver do unbounded work.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DecodeLimits {
+    /// Largest message, in bytes, including a length the header merely claims.
+    pub max_size: usize,
+    pub max_bindings: usize,
+}
+
+impl DecodeLimits {
+    /// Checks the outer header of an encoded message before it is decoded.
+    pub(crate) fn check_size(&self, bytes: &[u8]) -> Result<(), SnmpError> {
+        let (tag, length, _) = match outer_header(bytes) {
+            Some(header) => header,
+            None => return Err(SnmpError::Decode("Missing message tag".to_owned())),
+        };
+        if tag != Tag::SEQUENCE {
+            return Err(SnmpError::MalformedMessage { found: tag });
+        }
+
+        self.check_length(bytes, length)
+    }
+
+    /// Checks the size of an encoded value against `max_size`, taking the
+    /// larger of its actual and declared `length`.
+    fn check_length(&self, bytes: &[u8], length: Option<usize>) -> Result<(), SnmpError> {
+        let size = length.unwrap_or(bytes.len()).max(bytes.len());
+        if size > self.max_size {
+            return Err(SnmpError::MessageTooLarge(format!(
+                "{} bytes exceeds the limit of {}",
+                size, self.max_size
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Decodes a `T` with `max_bindings` in force: a PDU with more bindings
+    /// is rejected with `MessageTooLarge` as soon as the limit is passed,
+    /// before the rest of its bindings are decoded.
+    pub(crate) fn decode<T: Decode>(&self, bytes: &[u8]) -> Result<T, SnmpError> {
+        let previous = BINDING_LIMIT.with(|limit| limit.replace(self.max_bindings));
+        BINDINGS_OVER_LIMIT.with(|over| over.set(false));
+        let decoded = Ber::decode(bytes);
+        BINDING_LIMIT.with(|limit| limit.set(previous));
+
+        if BINDINGS_OVER_LIMIT.with(|over| over.replace(false)) {
+            return Err(SnmpError::MessageTooLarge(format!(
+                "more than {} bindings exceeds the limit",
+                self.max_bindings
+            )));
+        }
+
+        decoded
+    }
+}
+
+const DEFAULT_MAX_BINDINGS: usize = 10_000;
+
+thread_local! {
+    // rasn's `Decode` takes no context, so `DecodeLimits::decode` leaves the
+    // limit here for `Pdu::decode`, which counts each PDU's bindings down
+    // from it and flags when one goes over.
+    static BINDING_LIMIT: Cell<usize> = const { Cell::new(DEFAULT_MAX_BINDINGS) };
+    static BINDINGS_LEFT: Cell<usize> = const { Cell::new(0) };
+    static BINDINGS_OVER_LIMIT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A `VarBinding` that counts itself against `BINDING_LIMIT` as it is decoded.
+struct LimitedBinding(VarBinding);
+
+impl AsnType for LimitedBinding {
+    const TAG: Tag = VarBinding::TAG;
+}
+
+impl Decode for LimitedBinding {
+    fn decode_with_tag<D: Decoder>(decoder: &mut D, tag: Tag) -> Result<Self, D::Error> {
+        let within = BINDINGS_LEFT.with(|left| match left.get() {
+            0 => false,
+            n => {
+                left.set(n - 1);
+                true
+            }
+        });
+        if !within {
+            BINDINGS_OVER_LIMIT.with(|over| over.set(true));
+            return Err(D::Error::custom("Too many bindings"));
+        }
+
+        VarBinding::decode_with_tag(decoder, tag).map(LimitedBinding)
+    }
+}
+
+impl Default for DecodeLimits {
+    fn default() -> DecodeLimits {
+        DecodeLimits {
+            max_size: 65535,
+            max_bindings: DEFAULT_MAX_BINDINGS,
+        }
+    }
+}
+
+/// The size a message declares in its outer header, counting the header
+/// itself. `None` if the header is missing, truncated or of indefinite length.
+pub(crate) fn declared_size(bytes: &[u8]) -> Option<usize> {
+    let (_, length, header) = outer_header(bytes)?;
+    length?.checked_add(header)
+}
+
+/// The tag of the PDU in an encoded community-based message, found from the
+/// headers alone, skipping the version and community.
+fn community_pdu_tag(bytes: &[u8]) -> Option<Tag> {
+    let (_, _, header) = outer_header(bytes)?;
+    let mut rest = bytes.get(header..)?;
+    for _ in 0..2 {
+        let (_, length, header) = outer_header(rest)?;
+        rest = rest.get(header.checked_add(length?)?..)?;
+    }
+    outer_header(rest).map(|(tag, _, _)| tag)
+}
+
+/// Reads the BER identifier and length at the start of `bytes`, and the size
+/// of the two. The length is `None` for the indefinite form; the whole header
+/// is `None` if it is missing or truncated.
+fn outer_header(bytes: &[u8]) -> Option<(Tag, Option<usize>, usize)> {
+    let (first, mut rest) = bytes.split_first()?;
+    let class = match first >> 6 {
+        0 => Class::Universal,
+        1 => Class::Application,
+        2 => Class::Context,
+        _ => Class::Private,
+    };
+
+    let tag = if first & 0x1f != 0x1f {
+        Tag::new(class, u32::from(first & 0x1f))
+    } else {
+        // High tag numbers continue in base-128, most significant group first.
+        let mut value: u32 = 0;
+        loop {
+            let (b, tail) = rest.split_first()?;
+            rest = tail;
+            value = value.checked_mul(128)? | u32::from(b & 0x7f);
+            if b & 0x80 == 0 {
+                break Tag::new(class, value);
+            }
+        }
+    };
+
+    let (first, rest) = rest.split_first()?;
+    let (length, long_octets) = match first {
+        0..=0x7f => (Some(usize::from(*first)), 0),
+        0x80 => (None, 0),
+        _ => {
+            let octets = rest.get(..usize::from(first & 0x7f))?;
+            let length = octets.iter().try_fold(0usize, |n, b| {
+                n.checked_mul(256).map(|n| n | usize::from(*b))
+            });
+            // A length too big to represent is certainly too big to accept.
+            (Some(length.unwrap_or(usize::MAX)), octets.len())
+        }
+    };
+
+    Some((tag, length, bytes.len() - rest.len() + long_octets))
 }
 
 impl AsnType for Message {
     const TAG: Tag = Tag::SEQUENCE;
 }
-impl<'a> Encode for Message {
+impl Encode for Message {
     fn encode_with_tag<E: Encoder>(&self, encoder: &mut E, tag: Tag) -> Result<(), E::Error> {
         encoder.encode_sequence(tag, |sequence| {
             self.version.encode(sequence)?;
-            SnmpString::new(self.community.clone()).encode(sequence)?;
+            self.community.encode(sequence)?;
             self.data.encode(sequence)?;
             Ok(())
         })?;
@@ -55,7 +352,7 @@ impl Decode for Message {
     fn decode_with_tag<D: Decoder>(decoder: &mut D, _: Tag) -> Result<Self, D::Error> {
         let mut seq = decoder.decode_sequence(Self::TAG)?;
         let version = Version::decode(&mut seq)?;
-        let community = (*SnmpString::decode(&mut seq)?).clone();
+        let community = OctetString::decode(&mut seq)?;
         let data = Pdu::decode(&mut seq)?;
 
         Ok(Message {
@@ -66,7 +363,7 @@ impl Decode for Message {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PduTag {
     GetRequest,
     GetNextRequest,
@@ -80,8 +377,8 @@ pub enum PduTag {
 }
 
 impl PduTag {
-    fn into_tag(&self) -> Tag {
-        match &self {
+    fn into_tag(self) -> Tag {
+        match self {
             PduTag::GetRequest => TAG_MSG_GET,
             PduTag::GetNextRequest => TAG_MSG_GETNEXT,
             PduTag::GetResponse => TAG_MSG_RESPONSE,
@@ -112,7 +409,7 @@ impl PduTag {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Pdu {
     tag: PduTag,
     request_id: i32,
@@ -144,11 +441,8 @@ impl Pdu {
     }
 
     pub fn with_null_bindings(mut self, bindings: &[ObjectIdentifier]) -> Self {
-        self.bindings.extend(
-            bindings
-                .into_iter()
-                .map(|b| VarBinding::null_from(b.clone())),
-        );
+        self.bindings
+            .extend(bindings.iter().map(|b| VarBinding::null_from(b.clone())));
         self
     }
 
@@ -162,6 +456,10 @@ impl Pdu {
         self.tag
     }
 
+    pub fn request_id(&self) -> i32 {
+        self.request_id
+    }
+
     pub fn error(&self) -> Result<(), i32> {
         if self.err_status == 0 {
             return Ok(());
@@ -170,9 +468,60 @@ impl Pdu {
         Err(self.err_status)
     }
 
+    pub fn error_index(&self) -> i32 {
+        self.err_index
+    }
+
+    /// The binding `error_index` points at, or `None` if it is 0, negative or
+    /// past the last binding.
+    pub fn error_binding(&self) -> Option<&VarBinding> {
+        let index = usize::try_from(self.err_index).ok()?;
+        self.bindings.get(index.checked_sub(1)?)
+    }
+
     pub fn bindings(&self) -> &[VarBinding] {
         &self.bindings
     }
+
+    /// The counter a Report PDU carries, such as `usmStatsUnknownEngineIDs`,
+    /// naming the reason the agent rejected a message. `None` for other PDUs.
+    pub fn report_stat(&self) -> Option<(ObjectIdentifier, Value)> {
+        if self.tag != PduTag::Report {
+            return None;
+        }
+
+        self.bindings
+            .first()
+            .map(|vb| (vb.name.clone(), vb.value.clone()))
+    }
+
+    /// The non-repeaters and max-repetitions of a GetBulk request.
+    pub fn bulk_repetitions(&self) -> (i32, i32) {
+        (self.err_status, self.err_index)
+    }
+
+    /// Encodes the PDU alone, for callers that frame the message themselves.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SnmpError> {
+        Ber::encode(self)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Pdu, SnmpError> {
+        Pdu::from_bytes_with_limits(bytes, DecodeLimits::default())
+    }
+
+    /// Like `from_bytes`, but rejects the PDU with `MessageTooLarge` if it
+    /// exceeds `limits`. SNMPv1 Trap-PDUs fail with `Unsupported`.
+    pub fn from_bytes_with_limits(bytes: &[u8], limits: DecodeLimits) -> Result<Pdu, SnmpError> {
+        let (tag, length) = match outer_header(bytes) {
+            Some((tag, length, _)) => (Some(tag), length),
+            None => (None, None),
+        };
+        if tag == Some(TAG_MSG_TRAPV1) {
+            return Err(SnmpError::Unsupported("SNMPv1 Trap-PDUs"));
+        }
+        limits.check_length(bytes, length)?;
+        limits.decode(bytes)
+    }
 }
 
 impl AsnType for Pdu {
@@ -204,7 +553,9 @@ impl Decode for Pdu {
         let err_status = i32::decode(&mut seq)?;
         let err_index = i32::decode(&mut seq)?;
 
-        let bindings: Vec<VarBinding> = seq.decode_sequence_of(VarBinding::TAG)?;
+        BINDINGS_LEFT.with(|left| left.set(BINDING_LIMIT.with(Cell::get)));
+        let bindings: Vec<LimitedBinding> = seq.decode_sequence_of(VarBinding::TAG)?;
+        let bindings = bindings.into_iter().map(|b| b.0).collect();
 
         Ok(Pdu {
             tag,
@@ -217,6 +568,9 @@ impl Decode for Pdu {
 }
 
 /* TODO: V1 support
+// agent_address is a NetworkAddress, which SNMPv1 only defines for IPv4. An
+// agent sending over IPv6 has to put 0.0.0.0 there, so a receiver should
+// report the UDP source address rather than trust this field.
 struct TrapV1 {
     enterprise: ObjectIdentifier,
     agent_address: Ipv4Addr,
@@ -230,8 +584,12 @@ struct TrapV1 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::Value;
-    use rasn::ber::encode;
+    use crate::mib::Syntax;
+    use crate::oid;
+    use crate::types::ObjectIdentifierExt;
+    use crate::usm::{USM_STATS_NOT_IN_TIME_WINDOWS, USM_STATS_UNKNOWN_ENGINE_IDS};
+
+    use rasn::ber::{decode, encode};
 
     #[test]
     fn encode_pdu() {
@@ -271,9 +629,321 @@ mod tests {
         assert_eq!(
             r,
             &[
-                48, 24, 2, 1, 2, 4, 6, 112, 117, 98, 108, 105, 99, 161, 11, 2, 1, 1, 2, 1, 0, 2, 1,
+                48, 24, 2, 1, 3, 4, 6, 112, 117, 98, 108, 105, 99, 161, 11, 2, 1, 1, 2, 1, 0, 2, 1,
                 0, 48, 0
             ]
         )
     }
+
+    #[test]
+    fn decode_message_with_oversized_arc() {
+        let vb = VarBinding::new(oid! {1,3,6,1,4294967295}, Value::Null);
+        let pdu = Pdu::new(PduTag::GetResponse, 1).with_bindings(&[vb]);
+        let mut b = Message::new(Version::V2C, "public", pdu)
+            .to_bytes()
+            .unwrap();
+
+        let max: &[u8] = &[0x8f, 0xff, 0xff, 0xff, 0x7f];
+        let i = b.windows(5).position(|w| w == max).unwrap();
+        b[i..i + 5].copy_from_slice(&[0x90, 0x80, 0x80, 0x80, 0x00]);
+
+        match Message::from_bytes(&b) {
+            Err(SnmpError::Decode(msg)) => assert!(msg.contains("32 bits"), "{}", msg),
+            r => panic!("Unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn report_stat() {
+        // usmStatsUnknownEngineIDs.0 = Counter32: 1
+        let b: &[u8] = &[
+            168, 28, 2, 1, 0, 2, 1, 0, 2, 1, 0, 48, 17, 48, 15, 6, 10, 43, 6, 1, 6, 3, 15, 1, 1, 4,
+            0, 65, 1, 1,
+        ];
+        let pdu = decode::<Pdu>(b).unwrap();
+        assert_eq!(pdu.tag(), PduTag::Report);
+        assert_eq!(
+            pdu.report_stat(),
+            Some((
+                ObjectIdentifier::new(USM_STATS_UNKNOWN_ENGINE_IDS.to_vec()).unwrap(),
+                Value::Counter32(1)
+            ))
+        );
+
+        // usmStatsNotInTimeWindows.0 = Counter32: 300
+        let b: &[u8] = &[
+            168, 29, 2, 1, 7, 2, 1, 0, 2, 1, 0, 48, 18, 48, 16, 6, 10, 43, 6, 1, 6, 3, 15, 1, 1, 2,
+            0, 65, 2, 1, 44,
+        ];
+        let pdu = decode::<Pdu>(b).unwrap();
+        let (name, value) = pdu.report_stat().unwrap();
+        assert_eq!(name.as_slice(), USM_STATS_NOT_IN_TIME_WINDOWS);
+        assert_eq!(value, Value::Counter32(300));
+
+        let vb = VarBinding::new(oid! {1,3,6,1,6,3,15,1,1,4,0}, Value::Counter32(1));
+        let response = Pdu::new(PduTag::GetResponse, 0).with_bindings(&[vb]);
+        assert_eq!(response.report_stat(), None);
+    }
+
+    #[test]
+    fn decode_message_with_wrong_outer_tag() {
+        let pdu = Pdu::new(PduTag::GetResponse, 1);
+        let mut b = Message::new(Version::V2C, "public", pdu)
+            .to_bytes()
+            .unwrap();
+        b[0] = 0xa2;
+
+        match Message::from_bytes(&b) {
+            Err(SnmpError::MalformedMessage { found }) => {
+                assert_eq!(found, Tag::new(Class::Context, 2))
+            }
+            r => panic!("Unexpected result {:?}", r),
+        }
+        assert!(matches!(
+            Message::from_bytes(&[]),
+            Err(SnmpError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn decode_message_with_forged_length() {
+        // A SEQUENCE claiming 2 GiB of content.
+        let b: &[u8] = &[48, 0x84, 0x7f, 0xff, 0xff, 0xff, 2, 1, 1];
+        assert!(matches!(
+            Message::from_bytes(b),
+            Err(SnmpError::MessageTooLarge(_))
+        ));
+
+        let bindings: Vec<_> = (0..4)
+            .map(|i| VarBinding::new(oid! {1,3,6,1}.child(&[i]), Value::Null))
+            .collect();
+        let pdu = Pdu::new(PduTag::GetResponse, 1).with_bindings(&bindings);
+        let b = Message::new(Version::V2C, "public", pdu)
+            .to_bytes()
+            .unwrap();
+        let limits = DecodeLimits {
+            max_bindings: 3,
+            ..DecodeLimits::default()
+        };
+        assert!(matches!(
+            Message::from_bytes_with_limits(&b, limits),
+            Err(SnmpError::MessageTooLarge(_))
+        ));
+        assert!(Message::from_bytes(&b).is_ok());
+
+        let b = Pdu::new(PduTag::GetResponse, 1)
+            .with_bindings(&bindings)
+            .to_bytes()
+            .unwrap();
+        assert!(matches!(
+            Pdu::from_bytes_with_limits(&b, limits),
+            Err(SnmpError::MessageTooLarge(_))
+        ));
+        assert_eq!(Pdu::from_bytes(&b).unwrap().bindings().len(), 4);
+    }
+
+    #[test]
+    fn v1_trap_is_unsupported() {
+        // A coldStart from enterprise 1.3.6.1.4.1.8072 with agent-address
+        // 0.0.0.0, as an agent sending over IPv6 has to fill it in.
+        let trap: &[u8] = &[
+            0xa4, 0x1a, 6, 7, 0x2b, 6, 1, 4, 1, 0xbf, 0x08, 0x40, 4, 0, 0, 0, 0, 2, 1, 0, 2, 1, 0,
+            0x43, 1, 0, 0x30, 0,
+        ];
+        let mut b = vec![0x30, 0x27, 2, 1, 0, 4, 6];
+        b.extend_from_slice(b"public");
+        b.extend_from_slice(trap);
+
+        assert!(matches!(
+            Message::from_bytes(&b),
+            Err(SnmpError::Unsupported(_))
+        ));
+        assert!(matches!(
+            Pdu::from_bytes(trap),
+            Err(SnmpError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn raw_community_round_trip() {
+        let community: &[u8] = &[b'p', 0xe9, b'c', 0xff];
+        let pdu = Pdu::new(PduTag::GetRequest, 1);
+        let b = Message::with_raw_community(Version::V2C, community, pdu)
+            .to_bytes()
+            .unwrap();
+        assert_eq!(&b[5..11], &[4, 4, b'p', 0xe9, b'c', 0xff]);
+
+        let msg = Message::from_bytes(&b).unwrap();
+        assert_eq!(msg.community_bytes(), community);
+        assert_eq!(msg.community(), "p\u{fffd}c\u{fffd}");
+    }
+
+    #[test]
+    fn inform_response() {
+        let vb = VarBinding::new(oid! {1,3,6,1,2,1,1,3,0}, Value::Integer(5));
+        let pdu = Pdu::new(PduTag::InformRequest, 77).with_bindings(&[vb]);
+        let inform = Message::new(Version::V2C, "traps", pdu);
+
+        let ack = inform.make_inform_response();
+        assert_eq!(ack.version(), Version::V2C);
+        assert_eq!(ack.community(), "traps");
+        assert_eq!(ack.data().tag(), PduTag::GetResponse);
+        assert_eq!(ack.data().request_id(), 77);
+        assert_eq!(ack.data().error(), Ok(()));
+        assert_eq!(ack.data().error_index(), 0);
+        assert_eq!(ack.data().bindings().len(), 1);
+        assert_eq!(ack.data().bindings()[0].name, oid! {1,3,6,1,2,1,1,3,0});
+        assert_eq!(ack.data().bindings()[0].value, Value::Integer(5));
+    }
+
+    #[test]
+    fn encoded_len() {
+        let bindings: Vec<_> = (0..200)
+            .map(|i| VarBinding::new(oid! {1,3,6,1,2,1,2,2,1,10}.child(&[i]), Value::Counter32(i)))
+            .collect();
+        let messages = vec![
+            Message::new(Version::V2C, "public", Pdu::new(PduTag::GetRequest, 1)),
+            Message::new(
+                Version::V1,
+                "a-longer-community",
+                Pdu::new(PduTag::GetNextRequest, 123_456).with_null_bindings(&[oid! {1,3,6,1}]),
+            ),
+            // Long enough for multi-byte BER lengths.
+            Message::new(
+                Version::V2C,
+                "public",
+                Pdu::new(PduTag::GetResponse, -1).with_bindings(&bindings),
+            ),
+        ];
+
+        assert_eq!(messages[0].encoded_len().unwrap(), 26);
+        for msg in messages {
+            assert_eq!(msg.encoded_len().unwrap(), encode(&msg).unwrap().len());
+        }
+    }
+
+    #[test]
+    fn get_request_pdu_round_trip() {
+        let pdu = Pdu::new(PduTag::GetRequest, 9).with_null_bindings(&[oid! {1,3,6,1,2,1,1,5,0}]);
+        let b = pdu.to_bytes().unwrap();
+        assert_eq!(b[0], 0xa0);
+
+        let pdu = Pdu::from_bytes(&b).unwrap();
+        assert_eq!(pdu.tag(), PduTag::GetRequest);
+        assert_eq!(pdu.request_id(), 9);
+        assert_eq!(pdu.bindings()[0].name, oid! {1,3,6,1,2,1,1,5,0});
+        assert_eq!(pdu.bindings()[0].value, Value::Null);
+    }
+
+    #[test]
+    fn get_response_pdu_round_trip() {
+        let vb = VarBinding::new(oid! {1,3,6,1,2,1,1,5,0}, Value::Integer(-3));
+        let pdu = Pdu::new(PduTag::GetResponse, 9)
+            .with_error(2, 1)
+            .with_bindings(&[vb]);
+        let b = pdu.to_bytes().unwrap();
+        assert_eq!(b[0], 0xa2);
+
+        let pdu = Pdu::from_bytes(&b).unwrap();
+        assert_eq!(pdu.tag(), PduTag::GetResponse);
+        assert_eq!(pdu.error(), Err(2));
+        assert_eq!(pdu.error_index(), 1);
+        assert_eq!(pdu.bindings()[0].value, Value::Integer(-3));
+    }
+
+    #[test]
+    fn agent_answers_decoded_request() {
+        let request = Message::new(
+            Version::V2C,
+            "public",
+            Pdu::new(PduTag::GetRequest, 4711).with_null_bindings(&[oid! {1,3,6,1,2,1,1,5,0}]),
+        )
+        .to_bytes()
+        .unwrap();
+
+        let request = Message::from_bytes(&request).unwrap();
+        assert_eq!(request.data().tag(), PduTag::GetRequest);
+        let bindings: Vec<_> = request
+            .data()
+            .bindings()
+            .iter()
+            .map(|vb| VarBinding::new(vb.name.clone(), Value::from("core-1")))
+            .collect();
+        let response = request.make_response(&bindings).to_bytes().unwrap();
+
+        let response = Message::from_bytes(&response).unwrap();
+        assert_eq!(response.version(), Version::V2C);
+        assert_eq!(response.community(), "public");
+        assert_eq!(response.data().tag(), PduTag::GetResponse);
+        assert_eq!(response.data().request_id(), 4711);
+        assert_eq!(response.data().error(), Ok(()));
+        assert_eq!(
+            response.data().bindings()[0].to_pair(),
+            ("1.3.6.1.2.1.1.5.0".to_owned(), Value::from("core-1"))
+        );
+    }
+
+    #[test]
+    fn error_binding_is_bounds_checked() {
+        let vb = VarBinding::null_from(oid! {1,3,6,1,2,1,1,5,0});
+        let pdu = |index| {
+            Pdu::new(PduTag::GetResponse, 1)
+                .with_error(3, index)
+                .with_bindings(std::slice::from_ref(&vb))
+        };
+
+        assert_eq!(pdu(1).error_binding().unwrap().name, vb.name);
+        assert!(pdu(0).error_binding().is_none());
+        assert!(pdu(2).error_binding().is_none());
+        assert!(pdu(-1).error_binding().is_none());
+        assert!(pdu(i32::MAX).error_binding().is_none());
+    }
+
+    #[test]
+    fn error_binding_in_get_response() {
+        let oids = [
+            oid! {1,3,6,1,2,1,1,1,0},
+            oid! {1,3,6,1,2,1,1,4,0},
+            oid! {1,3,6,1,2,1,1,5,0},
+        ];
+        let b = Pdu::new(PduTag::GetResponse, 9)
+            .with_error(2, 2)
+            .with_null_bindings(&oids)
+            .to_bytes()
+            .unwrap();
+
+        let pdu = Pdu::from_bytes(&b).unwrap();
+        assert_eq!(pdu.error_binding().unwrap().name, oids[1]);
+    }
+
+    #[test]
+    fn debug_tree_of_response() {
+        let bindings = [
+            VarBinding::new(oid! {1,3,6,1,2,1,1,5,0}, Value::from("core-1")),
+            VarBinding::new(oid! {1,3,6,1,2,1,2,2,1,10,3}, Value::Counter32(42)),
+        ];
+        let pdu = Pdu::new(PduTag::GetResponse, 7).with_bindings(&bindings);
+        let msg = Message::new(Version::V2C, "public", pdu);
+        let mut registry = MibRegistry::new();
+        registry.insert(&[1, 3, 6, 1, 2, 1, 1, 5], "sysName", Syntax::OctetString);
+
+        let tree = msg.debug_tree_named(&registry);
+        assert_eq!(
+            tree.lines().collect::<Vec<_>>(),
+            [
+                "version: v2c",
+                "community: \"public\"",
+                "data: GetResponse",
+                "  request-id: 7",
+                "  error-status: noError (0)",
+                "  error-index: 0",
+                "  variable-bindings: 2 items",
+                "    sysName.0 (1.3.6.1.2.1.1.5.0)",
+                "      STRING: \"core-1\"",
+                "    1.3.6.1.2.1.2.2.1.10.3",
+                "      Counter32: 42",
+            ]
+        );
+        assert!(msg.debug_tree().contains("\n    1.3.6.1.2.1.1.5.0\n"));
+    }
 }
@@ -0,0 +1,115 @@
+//! Turning repeated counter samples into per-second rates.
+
+use crate::client::Client;
+use crate::error::SnmpError;
+use crate::transport::Transport;
+use crate::types::{ObjectIdentifier, Value};
+
+use std::net::UdpSocket;
+use std::time::Instant;
+
+/// Polls a fixed set of counters and reports how fast each has grown since
+/// the previous poll.
+pub struct RatePoller<'a, T: Transport = UdpSocket> {
+    client: Client<'a, T>,
+    oids: Vec<ObjectIdentifier>,
+    previous: Option<(Instant, Vec<Value>)>,
+}
+
+impl<'a, T: Transport> RatePoller<'a, T> {
+    pub fn new(client: Client<'a, T>, oids: Vec<ObjectIdentifier>) -> RatePoller<'a, T> {
+        RatePoller {
+            client,
+            oids,
+            previous: None,
+        }
+    }
+
+    pub fn into_client(self) -> Client<'a, T> {
+        self.client
+    }
+
+    /// GETs the counters and returns the per-second rate of each since the
+    /// last call, using `Value::counter_delta` so a single wrap is handled.
+    ///
+    /// The first call only records a sample and returns nothing. OIDs whose
+    /// value is missing, not a counter, or changed width are left out.
+    pub fn poll(&mut self) -> Result<Vec<(ObjectIdentifier, f64)>, SnmpError> {
+        self.poll_at(self.client.now())
+    }
+
+    fn poll_at(&mut self, now: Instant) -> Result<Vec<(ObjectIdentifier, f64)>, SnmpError> {
+        let values: Vec<_> = self
+            .client
+            .get(&self.oids)?
+            .into_iter()
+            .map(|var| var.value)
+            .collect();
+
+        let mut rates = vec![];
+        if let Some((then, previous)) = self.previous.take() {
+            let secs = now.duration_since(then).as_secs_f64();
+            if secs > 0.0 {
+                for ((oid, value), previous) in self.oids.iter().zip(&values).zip(&previous) {
+                    if let Some(delta) = value.counter_delta(previous) {
+                        rates.push((oid.clone(), delta as f64 / secs));
+                    }
+                }
+            }
+        }
+
+        self.previous = Some((now, values));
+        Ok(rates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oid;
+    use crate::testing::*;
+    use crate::types::{VarBinding, Version};
+
+    use std::time::Duration;
+
+    #[test]
+    fn rates_from_consecutive_polls() {
+        let in_octets = oid! {1,3,6,1,2,1,2,2,1,10,1};
+        let hc_in_octets = oid! {1,3,6,1,2,1,31,1,1,1,6,1};
+        let in_speed = oid! {1,3,6,1,2,1,2,2,1,5,1};
+
+        let samples = vec![
+            vec![
+                Value::Counter32(u32::MAX - 99),
+                Value::Counter64(1000),
+                Value::Gauge32(10),
+            ],
+            vec![
+                Value::Counter32(900),
+                Value::Counter64(6000),
+                Value::Gauge32(20),
+            ],
+        ];
+        let mut samples = samples.into_iter();
+        let mut socket = agent(move |request| {
+            let values = samples.next().unwrap();
+            let bindings: Vec<_> = request
+                .data()
+                .bindings()
+                .iter()
+                .zip(values)
+                .map(|(vb, value)| VarBinding::new(vb.name.clone(), value))
+                .collect();
+            response(request, &bindings)
+        });
+        let c = Client::new(Version::V2C, &mut socket);
+        let mut poller =
+            RatePoller::new(c, vec![in_octets.clone(), hc_in_octets.clone(), in_speed]);
+
+        let start = Instant::now();
+        assert!(poller.poll_at(start).unwrap().is_empty());
+
+        let rates = poller.poll_at(start + Duration::from_secs(10)).unwrap();
+        assert_eq!(rates, vec![(in_octets, 100.0), (hc_in_octets, 500.0)]);
+    }
+}
@@ -0,0 +1,233 @@
+//! JSON-friendly (de)serialization of values and bindings.
+//!
+//! OIDs are written as dotted strings and values as `{ "type": ..., "value": ... }`.
+//! Octet strings that are valid UTF-8 are written as text under the `OctetStr`
+//! type, anything else is written as hex under `HexStr`. Opaque values are always hex,
+//! as are the contents of `Unknown` values, whose class is `"Application"` or `"Private"`.
+
+use crate::types::{parse_oid, ObjectIdentifierDisplayWrapper, TimeTicks, Value, VarBinding};
+
+use rasn::types::{Class, OctetString};
+use rasn::Tag;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use std::net::Ipv4Addr;
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
+enum ValueRepr {
+    Null,
+    Oid(String),
+    Integer(i32),
+    IpAddr(Ipv4Addr),
+    Gauge32(u32),
+    Counter32(u32),
+    Counter64(u64),
+    Timeticks(u32),
+    OctetStr(String),
+    HexStr(String),
+    Opaque(String),
+    NoSuchObject,
+    NoSuchInstance,
+    EndOfMIBView,
+    Unknown {
+        class: String,
+        tag: u32,
+        bytes: String,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct VarBindingRepr {
+    oid: String,
+    value: Value,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..s.len())
+        .step_by(2)
+        .map(|i| s.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+        .collect()
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let repr = match self {
+            Value::Null => ValueRepr::Null,
+            Value::Oid(v) => ValueRepr::Oid(ObjectIdentifierDisplayWrapper(v).to_string()),
+            Value::Integer(v) => ValueRepr::Integer(*v),
+            Value::IpAddr(v) => ValueRepr::IpAddr(*v),
+            Value::Gauge32(v) => ValueRepr::Gauge32(*v),
+            Value::Counter32(v) => ValueRepr::Counter32(*v),
+            Value::Counter64(v) => ValueRepr::Counter64(*v),
+            Value::Timeticks(v) => ValueRepr::Timeticks(v.ticks()),
+            Value::OctetStr(v) => match std::str::from_utf8(v) {
+                Ok(s) => ValueRepr::OctetStr(s.to_owned()),
+                Err(_) => ValueRepr::HexStr(to_hex(v)),
+            },
+            Value::Opaque(v) => ValueRepr::Opaque(to_hex(v)),
+            Value::NoSuchObject => ValueRepr::NoSuchObject,
+            Value::NoSuchInstance => ValueRepr::NoSuchInstance,
+            Value::EndOfMIBView => ValueRepr::EndOfMIBView,
+            Value::Unknown { tag, bytes } => ValueRepr::Unknown {
+                class: format!("{:?}", tag.class),
+                tag: tag.value,
+                bytes: to_hex(bytes),
+            },
+        };
+
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = |s: String| {
+            from_hex(&s)
+                .map(OctetString::from)
+                .ok_or_else(|| D::Error::custom(format!("Invalid hex string {:?}", s)))
+        };
+
+        let value = match ValueRepr::deserialize(deserializer)? {
+            ValueRepr::Null => Value::Null,
+            ValueRepr::Oid(s) => Value::Oid(
+                parse_oid(&s).ok_or_else(|| D::Error::custom(format!("Invalid OID {:?}", s)))?,
+            ),
+            ValueRepr::Integer(v) => Value::Integer(v),
+            ValueRepr::IpAddr(v) => Value::IpAddr(v),
+            ValueRepr::Gauge32(v) => Value::Gauge32(v),
+            ValueRepr::Counter32(v) => Value::Counter32(v),
+            ValueRepr::Counter64(v) => Value::Counter64(v),
+            ValueRepr::Timeticks(v) => Value::Timeticks(TimeTicks::new(v)),
+            ValueRepr::OctetStr(s) => Value::OctetStr(OctetString::from(s.into_bytes())),
+            ValueRepr::HexStr(s) => Value::OctetStr(hex(s)?),
+            ValueRepr::Opaque(s) => Value::Opaque(hex(s)?),
+            ValueRepr::NoSuchObject => Value::NoSuchObject,
+            ValueRepr::NoSuchInstance => Value::NoSuchInstance,
+            ValueRepr::EndOfMIBView => Value::EndOfMIBView,
+            ValueRepr::Unknown { class, tag, bytes } => {
+                let class = match class.as_str() {
+                    "Application" => Class::Application,
+                    "Private" => Class::Private,
+                    _ => return Err(D::Error::custom(format!("Invalid class {:?}", class))),
+                };
+                Value::Unknown {
+                    tag: Tag::new(class, tag),
+                    bytes: hex(bytes)?,
+                }
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+impl Serialize for VarBinding {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        VarBindingRepr {
+            oid: ObjectIdentifierDisplayWrapper(&self.name).to_string(),
+            value: self.value.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for VarBinding {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = VarBindingRepr::deserialize(deserializer)?;
+        let name = parse_oid(&repr.oid)
+            .ok_or_else(|| D::Error::custom(format!("Invalid OID {:?}", repr.oid)))?;
+
+        Ok(VarBinding::new(name, repr.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oid;
+    use crate::types::ObjectIdentifier;
+
+    fn round_trip(v: Value, json: &str) {
+        let s = serde_json::to_string(&v).unwrap();
+        assert_eq!(s, json);
+
+        let d: Value = serde_json::from_str(&s).unwrap();
+        assert_eq!(d, v);
+    }
+
+    #[test]
+    fn value_round_trip() {
+        round_trip(Value::Null, r#"{"type":"Null"}"#);
+        round_trip(
+            Value::Oid(oid! {1,3,6,1}),
+            r#"{"type":"Oid","value":"1.3.6.1"}"#,
+        );
+        round_trip(Value::Integer(-5), r#"{"type":"Integer","value":-5}"#);
+        round_trip(
+            Value::IpAddr(Ipv4Addr::new(10, 0, 0, 1)),
+            r#"{"type":"IpAddr","value":"10.0.0.1"}"#,
+        );
+        round_trip(Value::Gauge32(128), r#"{"type":"Gauge32","value":128}"#);
+        round_trip(Value::Counter32(1), r#"{"type":"Counter32","value":1}"#);
+        round_trip(
+            Value::Counter64(1 << 32),
+            r#"{"type":"Counter64","value":4294967296}"#,
+        );
+        round_trip(
+            Value::Timeticks(TimeTicks::new(1234)),
+            r#"{"type":"Timeticks","value":1234}"#,
+        );
+        round_trip(
+            Value::OctetStr(OctetString::from_static(b"eth0")),
+            r#"{"type":"OctetStr","value":"eth0"}"#,
+        );
+        round_trip(
+            Value::OctetStr(OctetString::from_static(&[0, 0x1b, 0xff])),
+            r#"{"type":"HexStr","value":"001bff"}"#,
+        );
+        round_trip(
+            Value::Opaque(OctetString::from_static(&[3, 4, 5])),
+            r#"{"type":"Opaque","value":"030405"}"#,
+        );
+        round_trip(Value::NoSuchObject, r#"{"type":"NoSuchObject"}"#);
+        round_trip(Value::NoSuchInstance, r#"{"type":"NoSuchInstance"}"#);
+        round_trip(Value::EndOfMIBView, r#"{"type":"EndOfMIBView"}"#);
+        round_trip(
+            Value::Unknown {
+                tag: Tag::new(Class::Private, 2),
+                bytes: OctetString::from_static(&[0xab]),
+            },
+            r#"{"type":"Unknown","value":{"class":"Private","tag":2,"bytes":"ab"}}"#,
+        );
+    }
+
+    #[test]
+    fn binding_round_trip() {
+        let vb = VarBinding::new(oid! {1,3,6,1,2,1,1,5,0}, Value::Integer(7));
+        let s = serde_json::to_string(&vb).unwrap();
+        assert_eq!(
+            s,
+            r#"{"oid":"1.3.6.1.2.1.1.5.0","value":{"type":"Integer","value":7}}"#
+        );
+
+        let d: VarBinding = serde_json::from_str(&s).unwrap();
+        assert_eq!(d.name, vb.name);
+        assert_eq!(d.value, vb.value);
+    }
+
+    #[test]
+    fn reject_invalid_input() {
+        assert!(serde_json::from_str::<Value>(r#"{"type":"Oid","value":"1.x"}"#).is_err());
+        assert!(serde_json::from_str::<Value>(r#"{"type":"HexStr","value":"abc"}"#).is_err());
+    }
+}
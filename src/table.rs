@@ -0,0 +1,93 @@
+//! Reshaping the bindings of a table walk.
+
+use crate::types::{ObjectIdentifier, ObjectIdentifierExt, Value, VarBinding};
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+/// The OIDs of `columns` under `entry`, built on first use and kept in
+/// `roots` for the life of the process, as a poller walks the same tables
+/// every interval.
+pub(crate) fn column_roots(
+    roots: &'static OnceLock<Vec<ObjectIdentifier>>,
+    entry: &[u32],
+    columns: &[u32],
+) -> &'static [ObjectIdentifier] {
+    roots.get_or_init(|| {
+        let entry = ObjectIdentifier::new(entry.to_vec()).unwrap();
+        columns.iter().map(|c| entry.child(&[*c])).collect()
+    })
+}
+
+/// Groups the bindings of a walk of `table_entry` by column, keyed by the
+/// column's sub-identifier. Each column holds the index arcs and value of its
+/// cells in the order of `bindings`.
+///
+/// Bindings outside `table_entry`, or without index arcs after the column,
+/// are skipped.
+pub fn group_by_column(
+    bindings: &[VarBinding],
+    table_entry: &ObjectIdentifier,
+) -> BTreeMap<u32, Vec<(Vec<u32>, Value)>> {
+    let mut columns: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for vb in bindings {
+        let arcs = match vb.name.as_slice().strip_prefix(table_entry.as_slice()) {
+            Some(arcs) if arcs.len() >= 2 => arcs,
+            _ => continue,
+        };
+
+        columns
+            .entry(arcs[0])
+            .or_default()
+            .push((arcs[1..].to_vec(), vb.value.clone()));
+    }
+    columns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oid;
+
+    #[test]
+    fn column_roots_are_built_once() {
+        static ROOTS: OnceLock<Vec<ObjectIdentifier>> = OnceLock::new();
+        let roots = column_roots(&ROOTS, &[1, 3, 6, 1, 2, 1, 2, 2, 1], &[2, 5]);
+        assert_eq!(
+            roots,
+            [oid! {1,3,6,1,2,1,2,2,1,2}, oid! {1,3,6,1,2,1,2,2,1,5}]
+        );
+
+        let again = column_roots(&ROOTS, &[1, 3, 6, 1, 2, 1, 2, 2, 1], &[2, 5]);
+        assert!(std::ptr::eq(roots, again));
+    }
+
+    #[test]
+    fn groups_cells_by_column() {
+        let entry = oid! {1,3,6,1,2,1,2,2,1};
+        let cell =
+            |column: u32, index: u32, value| VarBinding::new(entry.child(&[column, index]), value);
+        let bindings = vec![
+            cell(2, 1, Value::from("lo")),
+            cell(5, 1, Value::Gauge32(10_000_000)),
+            cell(2, 2, Value::from("eth0")),
+            VarBinding::new(oid! {1,3,6,1,2,1,2,1,0}, Value::Integer(2)),
+            VarBinding::new(entry.child(&[7]), Value::Integer(1)),
+            cell(5, 2, Value::Gauge32(1_000_000_000)),
+        ];
+
+        let columns = group_by_column(&bindings, &entry);
+        assert_eq!(columns.keys().copied().collect::<Vec<_>>(), [2, 5]);
+        assert_eq!(
+            columns[&2],
+            [(vec![1], Value::from("lo")), (vec![2], Value::from("eth0"))]
+        );
+        assert_eq!(
+            columns[&5],
+            [
+                (vec![1], Value::Gauge32(10_000_000)),
+                (vec![2], Value::Gauge32(1_000_000_000))
+            ]
+        );
+    }
+}
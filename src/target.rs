@@ -0,0 +1,161 @@
+//! Per-device client settings kept together in one place.
+
+use crate::builder::ClientBuilder;
+use crate::client::Client;
+use crate::error::SnmpError;
+use crate::types::Version;
+
+use std::collections::BTreeMap;
+use std::net::{SocketAddr, UdpSocket};
+use std::time::Duration;
+
+/// How to reach and talk to one agent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TargetConfig {
+    pub address: SocketAddr,
+    pub version: Version,
+    pub read_community: String,
+    pub write_community: String,
+    pub timeout: Option<Duration>,
+    pub retries: u32,
+}
+
+impl TargetConfig {
+    /// A target using the same defaults as `ClientBuilder`.
+    pub fn new(address: SocketAddr, version: Version) -> TargetConfig {
+        TargetConfig {
+            address,
+            version,
+            read_community: "public".to_owned(),
+            write_community: "private".to_owned(),
+            timeout: None,
+            retries: 0,
+        }
+    }
+
+    pub fn communities(mut self, read_community: &str, write_community: &str) -> Self {
+        self.read_community = read_community.to_owned();
+        self.write_community = write_community.to_owned();
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// A builder carrying this target's settings, for anything not covered here.
+    pub fn builder(&self) -> ClientBuilder<'_> {
+        ClientBuilder::new(self.version)
+            .communities(&self.read_community, &self.write_community)
+            .timeout(self.timeout)
+            .retries(self.retries)
+    }
+
+    /// Opens a socket connected to the target.
+    pub fn connect(&self) -> Result<UdpSocket, SnmpError> {
+        self.builder().connect(self.address)
+    }
+
+    /// Creates a client for the target using `socket`, usually one returned
+    /// by `connect`.
+    pub fn client<'a>(&'a self, socket: &'a mut UdpSocket) -> Client<'a> {
+        self.builder().build(socket)
+    }
+}
+
+/// A set of named targets.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Targets {
+    targets: BTreeMap<String, TargetConfig>,
+}
+
+impl Targets {
+    pub fn new() -> Targets {
+        Targets::default()
+    }
+
+    /// Adds or replaces the target called `name`.
+    pub fn insert(&mut self, name: &str, config: TargetConfig) {
+        self.targets.insert(name.to_owned(), config);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TargetConfig> {
+        self.targets.get(name)
+    }
+
+    /// Iterates over the targets in name order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &TargetConfig)> {
+        self.targets
+            .iter()
+            .map(|(name, config)| (name.as_str(), config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oid;
+    use crate::pdu::Message;
+    use crate::testing::*;
+    use crate::types::ObjectIdentifier;
+
+    use std::sync::mpsc;
+
+    /// Spawns an agent that reports the version and community of the request
+    /// it receives.
+    fn reporting_agent() -> (SocketAddr, mpsc::Receiver<(Version, String)>) {
+        let (tx, rx) = mpsc::channel();
+        let socket = agent(move |request: &Message| {
+            tx.send((request.version(), request.community().into_owned()))
+                .unwrap();
+            response(request, request.data().bindings())
+        });
+        (socket.peer_addr().unwrap(), rx)
+    }
+
+    #[test]
+    fn clients_for_two_targets() {
+        let (core, core_requests) = reporting_agent();
+        let (edge, edge_requests) = reporting_agent();
+
+        let mut targets = Targets::new();
+        targets.insert(
+            "core",
+            TargetConfig::new(core, Version::V2C).communities("core-ro", "core-rw"),
+        );
+        targets.insert(
+            "edge",
+            TargetConfig::new(edge, Version::V1)
+                .communities("edge-ro", "edge-rw")
+                .timeout(Some(Duration::from_secs(2))),
+        );
+        assert_eq!(
+            targets.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            ["core", "edge"]
+        );
+
+        for (name, config) in targets.iter() {
+            let mut socket = config.connect().unwrap();
+            assert_eq!(socket.peer_addr().unwrap(), config.address, "{}", name);
+
+            let mut c = config.client(&mut socket);
+            c.get(&[oid! {1,3,6,1,2,1,1,5,0}]).unwrap();
+        }
+
+        assert_eq!(
+            core_requests.recv().unwrap(),
+            (Version::V2C, "core-ro".to_owned())
+        );
+        assert_eq!(
+            edge_requests.recv().unwrap(),
+            (Version::V1, "edge-ro".to_owned())
+        );
+        assert!(targets.get("missing").is_none());
+    }
+}
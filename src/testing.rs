@@ -0,0 +1,230 @@
+//! A local agent for exercising the client against real sockets in tests.
+
+use crate::pdu::{Message, Pdu, PduTag};
+use crate::transport::Transport;
+use crate::types::{ObjectIdentifier, Value, VarBinding, Version};
+
+use rasn::ber::{decode, encode};
+
+use std::collections::VecDeque;
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::thread;
+use std::time::Duration;
+
+pub(crate) fn local_socket() -> UdpSocket {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket.connect(socket.local_addr().unwrap()).unwrap();
+    socket
+}
+
+/// Spawns a local agent answering every request with `respond`, and returns
+/// a socket connected to it.
+pub(crate) fn agent<F>(mut respond: F) -> UdpSocket
+where
+    F: FnMut(&Message) -> Message + Send + 'static,
+{
+    agent_with_replies(move |request| vec![respond(request)])
+}
+
+/// Like `agent`, but each request may be answered with any number of datagrams.
+pub(crate) fn agent_with_replies<F>(mut respond: F) -> UdpSocket
+where
+    F: FnMut(&Message) -> Vec<Message> + Send + 'static,
+{
+    raw_agent(move |request| {
+        let request = decode::<Message>(request).unwrap();
+        respond(&request)
+            .iter()
+            .map(|response| encode(response).unwrap())
+            .collect()
+    })
+}
+
+/// Like `agent_with_replies`, for requests that are not community-based
+/// messages.
+pub(crate) fn raw_agent<F>(mut respond: F) -> UdpSocket
+where
+    F: FnMut(&[u8]) -> Vec<Vec<u8>> + Send + 'static,
+{
+    let agent = UdpSocket::bind("127.0.0.1:0").unwrap();
+    agent
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket.connect(agent.local_addr().unwrap()).unwrap();
+
+    thread::spawn(move || {
+        let mut buf = [0u8; 1500];
+        while let Ok((size, peer)) = agent.recv_from(&mut buf) {
+            for response in respond(&buf[..size]) {
+                agent.send_to(&response, peer).unwrap();
+            }
+        }
+    });
+
+    socket
+}
+
+type Respond = Box<dyn FnMut(&[u8]) -> Vec<Vec<u8>>>;
+
+/// A transport answering every datagram sent with `respond` in the calling
+/// thread, for deterministic tests. `recv` with no reply queued fails with
+/// `WouldBlock` straight away, so a dropped request times out immediately.
+pub(crate) struct MockTransport {
+    respond: Respond,
+    replies: VecDeque<Vec<u8>>,
+    /// Every datagram sent, in order.
+    pub(crate) sent: Vec<Vec<u8>>,
+    /// Where the last `send_to` went, which replies then appear to come from.
+    peer: Option<SocketAddr>,
+}
+
+impl MockTransport {
+    pub(crate) fn new<F>(respond: F) -> MockTransport
+    where
+        F: FnMut(&[u8]) -> Vec<Vec<u8>> + 'static,
+    {
+        MockTransport {
+            respond: Box::new(respond),
+            replies: VecDeque::new(),
+            sent: vec![],
+            peer: None,
+        }
+    }
+
+    /// Like `agent_with_replies`, for community-based messages.
+    pub(crate) fn agent<F>(mut respond: F) -> MockTransport
+    where
+        F: FnMut(&Message) -> Vec<Message> + 'static,
+    {
+        MockTransport::new(move |request| {
+            let request = decode::<Message>(request).unwrap();
+            respond(&request)
+                .iter()
+                .map(|response| encode(response).unwrap())
+                .collect()
+        })
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sent.push(buf.to_vec());
+        let replies = (self.respond)(buf);
+        self.replies.extend(replies);
+        Ok(buf.len())
+    }
+
+    fn recv(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let reply = match self.replies.pop_front() {
+            Some(reply) => reply,
+            None => return Err(io::ErrorKind::WouldBlock.into()),
+        };
+
+        let size = reply.len().min(buf.len());
+        buf[..size].copy_from_slice(&reply[..size]);
+        Ok(size)
+    }
+
+    fn send_to(&mut self, buf: &[u8], addr: SocketAddr) -> io::Result<usize> {
+        self.peer = Some(addr);
+        self.send(buf)
+    }
+
+    fn recv_from(&mut self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        let size = self.recv(buf)?;
+        let peer = self.peer.unwrap_or_else(|| ([127, 0, 0, 1], 161).into());
+        Ok((size, peer))
+    }
+}
+
+pub(crate) fn response(request: &Message, bindings: &[VarBinding]) -> Message {
+    request.make_response(bindings)
+}
+
+/// Answers GetNext requests from a sorted list of bindings, signalling the
+/// end of the MIB the way an agent of the request's version would.
+pub(crate) fn get_next_agent(mib: Vec<VarBinding>) -> impl FnMut(&Message) -> Message {
+    move |request| {
+        let mut bindings = vec![];
+        for (i, requested) in request.data().bindings().iter().enumerate() {
+            match mib
+                .iter()
+                .find(|vb| vb.name.as_slice() > requested.name.as_slice())
+            {
+                Some(vb) => bindings.push(vb.clone()),
+                None if request.version() == Version::V1 => {
+                    let pdu = Pdu::new(PduTag::GetResponse, request.data().request_id())
+                        .with_error(2, i as i32 + 1)
+                        .with_bindings(request.data().bindings());
+                    return Message::with_raw_community(
+                        request.version(),
+                        request.community_bytes(),
+                        pdu,
+                    );
+                }
+                None => bindings.push(VarBinding::new(requested.name.clone(), Value::EndOfMIBView)),
+            }
+        }
+
+        response(request, &bindings)
+    }
+}
+
+/// Answers Get requests from a list of bindings. Missing OIDs are reported the
+/// way an agent of the request's version would.
+pub(crate) fn get_agent(mib: Vec<VarBinding>) -> impl FnMut(&Message) -> Message {
+    move |request| {
+        let mut bindings = vec![];
+        for (i, requested) in request.data().bindings().iter().enumerate() {
+            match mib.iter().find(|vb| vb.name == requested.name) {
+                Some(vb) => bindings.push(vb.clone()),
+                None if request.version() == Version::V1 => {
+                    let pdu = Pdu::new(PduTag::GetResponse, request.data().request_id())
+                        .with_error(2, i as i32 + 1)
+                        .with_bindings(request.data().bindings());
+                    return Message::with_raw_community(
+                        request.version(),
+                        request.community_bytes(),
+                        pdu,
+                    );
+                }
+                None => bindings.push(VarBinding::new(requested.name.clone(), Value::NoSuchObject)),
+            }
+        }
+
+        response(request, &bindings)
+    }
+}
+
+/// Answers GetBulk requests from a sorted list of bindings, following each
+/// repeater until `max-repetitions` or the end of the MIB.
+pub(crate) fn get_bulk_agent(mib: Vec<VarBinding>) -> impl FnMut(&Message) -> Message {
+    move |request| {
+        let (non_repeaters, max_repetitions) = request.data().bulk_repetitions();
+        let next = |name: &ObjectIdentifier| match mib
+            .iter()
+            .find(|vb| vb.name.as_slice() > name.as_slice())
+        {
+            Some(vb) => vb.clone(),
+            None => VarBinding::new(name.clone(), Value::EndOfMIBView),
+        };
+
+        let (non_repeating, repeating) = request
+            .data()
+            .bindings()
+            .split_at(non_repeaters.max(0) as usize);
+        let mut bindings: Vec<_> = non_repeating.iter().map(|vb| next(&vb.name)).collect();
+        let mut last: Vec<_> = repeating.iter().map(|vb| vb.name.clone()).collect();
+        for _ in 0..max_repetitions {
+            for name in last.iter_mut() {
+                let vb = next(name);
+                *name = vb.name.clone();
+                bindings.push(vb);
+            }
+        }
+
+        response(request, &bindings)
+    }
+}
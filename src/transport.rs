@@ -0,0 +1,67 @@
+//! The datagram channel between a client and its agent.
+
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::time::Duration;
+
+/// Carries the datagrams of a `Client`. Implemented for `UdpSocket`; other
+/// implementations can script an agent in tests or tunnel SNMP elsewhere.
+///
+/// Only `send` and `recv` are required. The rest default to what a transport
+/// that is always connected and never blocks needs.
+pub trait Transport {
+    fn send(&mut self, buf: &[u8]) -> io::Result<usize>;
+
+    /// Receives one datagram. When none arrives in time this fails with
+    /// `WouldBlock` or `TimedOut`, which the client reports as a timeout.
+    fn recv(&mut self, buf: &mut [u8]) -> io::Result<usize>;
+
+    /// Sets how long `recv` may block. `None` blocks until a datagram arrives.
+    fn set_read_timeout(&mut self, _timeout: Option<Duration>) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_nonblocking(&mut self, _nonblocking: bool) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Sends to `addr`, for `Client::set_unconnected_agent`.
+    fn send_to(&mut self, buf: &[u8], _addr: SocketAddr) -> io::Result<usize> {
+        self.send(buf)
+    }
+
+    /// Receives one datagram and its sender, for
+    /// `Client::set_unconnected_agent`.
+    fn recv_from(&mut self, _buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "transport cannot report the sender of a datagram",
+        ))
+    }
+}
+
+impl Transport for UdpSocket {
+    fn send(&mut self, buf: &[u8]) -> io::Result<usize> {
+        UdpSocket::send(self, buf)
+    }
+
+    fn recv(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        UdpSocket::recv(self, buf)
+    }
+
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        UdpSocket::set_read_timeout(self, timeout)
+    }
+
+    fn set_nonblocking(&mut self, nonblocking: bool) -> io::Result<()> {
+        UdpSocket::set_nonblocking(self, nonblocking)
+    }
+
+    fn send_to(&mut self, buf: &[u8], addr: SocketAddr) -> io::Result<usize> {
+        UdpSocket::send_to(self, buf, addr)
+    }
+
+    fn recv_from(&mut self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        UdpSocket::recv_from(self, buf)
+    }
+}
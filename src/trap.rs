@@ -0,0 +1,320 @@
+//! Sending notifications, and recognising retransmitted ones on receipt.
+
+use crate::client::Client;
+use crate::error::SnmpError;
+use crate::mib2::SYS_UPTIME;
+use crate::pdu::{Message, Pdu, PduTag};
+use crate::transport::Transport;
+use crate::types::{ObjectIdentifier, TimeTicks, Value, VarBinding, Version};
+use crate::v3::V3Message;
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+/// `snmpTrapOID.0`, the second binding of every SNMPv2 notification.
+pub const SNMP_TRAP_OID: &[u32] = &[1, 3, 6, 1, 6, 3, 1, 1, 4, 1, 0];
+
+impl<'a, T: Transport> Client<'a, T> {
+    /// Sends an SNMPv2-Trap. `sysUpTime.0` and `snmpTrapOID.0` are prepended
+    /// to `bindings` from `uptime` and `trap_oid`.
+    pub fn send_trap_v2(
+        &mut self,
+        uptime: TimeTicks,
+        trap_oid: &ObjectIdentifier,
+        bindings: &[VarBinding],
+    ) -> Result<(), SnmpError> {
+        let mut all = Vec::with_capacity(bindings.len() + 2);
+        all.push(VarBinding::new(
+            ObjectIdentifier::new(SYS_UPTIME.to_vec()).unwrap(),
+            Value::Timeticks(uptime),
+        ));
+        all.push(VarBinding::new(
+            ObjectIdentifier::new(SNMP_TRAP_OID.to_vec()).unwrap(),
+            Value::Oid(trap_oid.clone()),
+        ));
+        all.extend_from_slice(bindings);
+
+        self.send_trap_v2_raw(&all)
+    }
+
+    /// Sends an SNMPv2-Trap carrying exactly `bindings`, for forwarding a
+    /// notification without rebuilding it. The bindings must already start
+    /// with `sysUpTime.0` and `snmpTrapOID.0`.
+    ///
+    /// Not available in SNMPv1, which has its own Trap-PDU, or in SNMPv3.
+    pub fn send_trap_v2_raw(&mut self, bindings: &[VarBinding]) -> Result<(), SnmpError> {
+        if self.version() == Version::V1 {
+            return Err(SnmpError::Unsupported("SNMPv2 traps in SNMPv1"));
+        }
+
+        match bindings {
+            [uptime, trap_oid, ..]
+                if uptime.name.as_slice() == SYS_UPTIME
+                    && trap_oid.name.as_slice() == SNMP_TRAP_OID => {}
+            _ => {
+                return Err(SnmpError::Encode(
+                    "SNMPv2 trap must start with sysUpTime.0 and snmpTrapOID.0".to_owned(),
+                ))
+            }
+        }
+
+        self.notify(PduTag::TrapV2, bindings)
+    }
+}
+
+impl Pdu {
+    /// The notification an SNMPv2-Trap or InformRequest is, from the value of
+    /// its `snmpTrapOID.0`. `None` for other PDUs, or when the second binding
+    /// is not `snmpTrapOID.0` with an OID value as RFC 3416 4.2.6 requires.
+    pub fn trap_oid(&self) -> Option<&ObjectIdentifier> {
+        if !matches!(self.tag(), PduTag::TrapV2 | PduTag::InformRequest) {
+            return None;
+        }
+
+        match self.bindings().get(1)? {
+            VarBinding {
+                name,
+                value: Value::Oid(trap_oid),
+            } if name.as_slice() == SNMP_TRAP_OID => Some(trap_oid),
+            _ => None,
+        }
+    }
+}
+
+impl Message {
+    /// The `trap_oid` of this message's PDU.
+    pub fn trap_oid(&self) -> Option<&ObjectIdentifier> {
+        self.data().trap_oid()
+    }
+}
+
+/// What identifies a received notification, so that retransmitted copies of
+/// it can be dropped.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NotificationKey {
+    /// SNMPv3 notifications are keyed on the contextEngineID of the device
+    /// that sent them, which stays the same when NAT changes its address.
+    Engine { engine_id: Vec<u8>, request_id: i32 },
+    /// Community-based notifications carry no engine id, so they are keyed on
+    /// the address they came from.
+    Source { source: SocketAddr, request_id: i32 },
+}
+
+impl NotificationKey {
+    pub fn of_v3(msg: &V3Message) -> NotificationKey {
+        let scoped = msg.scoped_pdu();
+        NotificationKey::Engine {
+            engine_id: scoped.context_engine_id().to_vec(),
+            request_id: scoped.data().request_id(),
+        }
+    }
+
+    pub fn of_community(source: SocketAddr, msg: &Message) -> NotificationKey {
+        NotificationKey::Source {
+            source,
+            request_id: msg.data().request_id(),
+        }
+    }
+}
+
+/// Remembers the notifications received within a window of time, to tell
+/// retransmissions, such as an unacknowledged inform being resent, from new
+/// ones.
+#[derive(Clone, Debug)]
+pub struct DuplicateFilter {
+    window: Duration,
+    seen: HashMap<NotificationKey, Instant>,
+}
+
+impl DuplicateFilter {
+    pub fn new(window: Duration) -> DuplicateFilter {
+        DuplicateFilter {
+            window,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Records a notification received at `now` and returns whether one with
+    /// the same key was already received within the window.
+    pub fn is_duplicate(&mut self, key: &NotificationKey, now: Instant) -> bool {
+        let window = self.window;
+        self.seen
+            .retain(|_, received| now.duration_since(*received) < window);
+
+        if self.seen.contains_key(key) {
+            return true;
+        }
+        self.seen.insert(key.clone(), now);
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oid;
+    use crate::testing::*;
+    use crate::v3::{ScopedPdu, SecurityParameters};
+
+    use std::net::UdpSocket;
+    use std::sync::mpsc;
+
+    type Received = (PduTag, Vec<VarBinding>);
+
+    fn trap_receiver() -> (UdpSocket, mpsc::Receiver<Received>) {
+        let (tx, rx) = mpsc::channel();
+        let socket = agent_with_replies(move |request| {
+            let data = request.data();
+            tx.send((data.tag(), data.bindings().to_vec())).unwrap();
+            vec![]
+        });
+        (socket, rx)
+    }
+
+    fn same(a: &[VarBinding], b: &[VarBinding]) -> bool {
+        a.len() == b.len()
+            && a.iter()
+                .zip(b)
+                .all(|(a, b)| a.name == b.name && a.value == b.value)
+    }
+
+    #[test]
+    fn v3_informs_deduplicated_by_engine_id() {
+        let inform = |request_id| {
+            let pdu = Pdu::new(PduTag::InformRequest, request_id).with_bindings(&[]);
+            let scoped = ScopedPdu::new(&[0x80, 0, 0x1f, 0x88, 4], "", pdu);
+            V3Message::new(9, SecurityParameters::default(), scoped)
+                .to_bytes()
+                .unwrap()
+        };
+        // An inform, its retransmission after the device's NAT mapping moved
+        // to another source port, and a new inform. The key ignores the source.
+        let received = [inform(5), inform(5), inform(6)];
+
+        let start = Instant::now();
+        let mut filter = DuplicateFilter::new(Duration::from_secs(30));
+        let duplicates: Vec<_> = received
+            .iter()
+            .map(|b| {
+                let key = NotificationKey::of_v3(&V3Message::from_bytes(b).unwrap());
+                filter.is_duplicate(&key, start)
+            })
+            .collect();
+        assert_eq!(duplicates, [false, true, false]);
+
+        let key = NotificationKey::of_v3(&V3Message::from_bytes(&inform(5)).unwrap());
+        assert!(!filter.is_duplicate(&key, start + Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn community_notifications_deduplicated_by_source() {
+        let trap = Message::new(Version::V2C, "public", Pdu::new(PduTag::TrapV2, 5));
+        let key = |source: &str| NotificationKey::of_community(source.parse().unwrap(), &trap);
+
+        let now = Instant::now();
+        let mut filter = DuplicateFilter::new(Duration::from_secs(30));
+        assert!(!filter.is_duplicate(&key("192.0.2.1:1024"), now));
+        assert!(filter.is_duplicate(&key("192.0.2.1:1024"), now));
+        assert!(!filter.is_duplicate(&key("192.0.2.1:1025"), now));
+    }
+
+    #[test]
+    fn send_trap_v2_prepends_mandatory_bindings() {
+        let (mut socket, rx) = trap_receiver();
+        let mut c = Client::new(Version::V2C, &mut socket);
+        let link_down = oid! {1,3,6,1,6,3,1,1,5,3};
+        let if_index = VarBinding::new(oid! {1,3,6,1,2,1,2,2,1,1,2}, Value::Integer(2));
+
+        c.send_trap_v2(
+            TimeTicks::new(100),
+            &link_down,
+            std::slice::from_ref(&if_index),
+        )
+        .unwrap();
+        let (tag, bindings) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(tag, PduTag::TrapV2);
+        assert_eq!(bindings.len(), 3);
+        assert_eq!(bindings[0].name.as_slice(), SYS_UPTIME);
+        assert_eq!(bindings[0].value, Value::Timeticks(TimeTicks::new(100)));
+        assert_eq!(bindings[1].name.as_slice(), SNMP_TRAP_OID);
+        assert_eq!(bindings[1].value, Value::Oid(link_down));
+        assert!(same(&bindings[2..], &[if_index]));
+    }
+
+    #[test]
+    fn send_trap_v2_is_unsupported_in_v3() {
+        let mut transport = MockTransport::new(|_| vec![]);
+        let mut c = Client::new(Version::V3, &mut transport);
+
+        let r = c.send_trap_v2(TimeTicks::new(100), &oid! {1,3,6,1,6,3,1,1,5,1}, &[]);
+        assert!(matches!(r, Err(SnmpError::Unsupported(_))));
+        assert!(transport.sent.is_empty());
+    }
+
+    #[test]
+    fn trap_oid_of_cold_start() {
+        let cold_start = oid! {1,3,6,1,6,3,1,1,5,1};
+        let bindings = vec![
+            VarBinding::new(
+                oid! {1,3,6,1,2,1,1,3,0},
+                Value::Timeticks(TimeTicks::new(12)),
+            ),
+            VarBinding::new(
+                ObjectIdentifier::new(SNMP_TRAP_OID.to_vec()).unwrap(),
+                Value::Oid(cold_start.clone()),
+            ),
+        ];
+        let trap = |tag, bindings: &[VarBinding]| {
+            let msg = Message::new(
+                Version::V2C,
+                "public",
+                Pdu::new(tag, 1).with_bindings(bindings),
+            );
+            Message::from_bytes(&msg.to_bytes().unwrap()).unwrap()
+        };
+
+        assert_eq!(
+            trap(PduTag::TrapV2, &bindings).trap_oid(),
+            Some(&cold_start)
+        );
+        assert_eq!(
+            trap(PduTag::InformRequest, &bindings).trap_oid(),
+            Some(&cold_start)
+        );
+        assert_eq!(trap(PduTag::GetResponse, &bindings).trap_oid(), None);
+        assert_eq!(trap(PduTag::TrapV2, &bindings[..1]).trap_oid(), None);
+
+        let mut not_an_oid = bindings.clone();
+        not_an_oid[1].value = Value::from("coldStart");
+        assert_eq!(trap(PduTag::TrapV2, &not_an_oid).trap_oid(), None);
+    }
+
+    #[test]
+    fn send_trap_v2_raw_passes_bindings_through() {
+        let (mut socket, rx) = trap_receiver();
+        let mut c = Client::new(Version::V2C, &mut socket);
+        let bindings = vec![
+            VarBinding::new(
+                oid! {1,3,6,1,2,1,1,3,0},
+                Value::Timeticks(TimeTicks::new(7)),
+            ),
+            VarBinding::new(
+                oid! {1,3,6,1,6,3,1,1,4,1,0},
+                Value::Oid(oid! {1,3,6,1,4,1,99,0,1}),
+            ),
+            VarBinding::new(oid! {1,3,6,1,4,1,99,1}, Value::from("relayed")),
+            VarBinding::new(
+                oid! {1,3,6,1,2,1,1,3,0},
+                Value::Timeticks(TimeTicks::new(5)),
+            ),
+        ];
+
+        c.send_trap_v2_raw(&bindings).unwrap();
+        let (_, received) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(same(&received, &bindings));
+
+        let r = c.send_trap_v2_raw(&bindings[2..]);
+        assert!(matches!(r, Err(SnmpError::Encode(_))));
+    }
+}
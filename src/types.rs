@@ -1,10 +1,12 @@
+use crate::error::SnmpError;
 use rasn::de::Error;
 use rasn::types::{Class, Implicit, Utf8String};
 pub use rasn::types::{ObjectIdentifier, OctetString};
 use rasn::{AsnType, Decode, Decoder, Encode, Encoder, Tag};
 use std::fmt;
+use std::str::FromStr;
 use std::time;
-use std::net::Ipv4Addr;
+use std::net::{Ipv4Addr, Ipv6Addr};
 
 /* Definitions from RFC 2578 */
 const TAG_IPADDR: Tag = Tag::new(Class::Application, 0);
@@ -17,7 +19,20 @@ const TAG_NOSUCHOBJECT: Tag = Tag::new(Class::Context, 0);
 const TAG_NOSUCHINSTANCE: Tag = Tag::new(Class::Context, 1);
 const TAG_ENDOFMIBVIEW: Tag = Tag::new(Class::Context, 2);
 
-struct ObjectIdentifierDisplayWrapper<'a>(&'a ObjectIdentifier);
+/// The application-class tags `Value` has a variant of its own for.
+const KNOWN_APPLICATION_TAGS: &[Tag] = &[
+    TAG_IPADDR,
+    TAG_COUNTER32,
+    TAG_GAUGE32,
+    TAG_TIMETICKS,
+    TAG_OPAQUE,
+    TAG_COUNTER64,
+];
+
+/// Tag of the Counter64 some agents wrap in an `Opaque` for SNMPv1 managers.
+const OPAQUE_COUNTER64: [u8; 2] = [0x9f, 0x76];
+
+pub(crate) struct ObjectIdentifierDisplayWrapper<'a>(pub(crate) &'a ObjectIdentifier);
 impl<'a> fmt::Display for ObjectIdentifierDisplayWrapper<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let subids = self.0;
@@ -28,12 +43,164 @@ impl<'a> fmt::Display for ObjectIdentifierDisplayWrapper<'a> {
     }
 }
 
+/// Encodes an OBJECT IDENTIFIER. rasn 0.2 emits the 7-bit groups of multi-byte
+/// sub-identifiers least significant first, so OIDs are encoded here instead.
+pub(crate) fn encode_oid<E: Encoder>(
+    encoder: &mut E,
+    oid: &ObjectIdentifier,
+) -> Result<(), E::Error> {
+    let mut bytes = vec![];
+    encode_subidentifier(u64::from(oid[0]) * 40 + u64::from(oid[1]), &mut bytes);
+    for arc in &oid[2..] {
+        encode_subidentifier(u64::from(*arc), &mut bytes);
+    }
+
+    encoder.encode_octet_string(Tag::OBJECT_IDENTIFIER, &bytes)?;
+    Ok(())
+}
+
+fn encode_subidentifier(mut arc: u64, bytes: &mut Vec<u8>) {
+    let start = bytes.len();
+    loop {
+        bytes.push(arc as u8 | 0x80);
+        arc >>= 7;
+        if arc == 0 {
+            break;
+        }
+    }
+
+    bytes[start..].reverse();
+    if let Some(last) = bytes.last_mut() {
+        *last &= 0x7f;
+    }
+}
+
+/// Decodes an OBJECT IDENTIFIER. Sub-identifiers are limited to 32 bits, as
+/// in SMIv2 and rasn's `ObjectIdentifier`, so larger arcs are rejected with a
+/// descriptive error instead of being truncated. Padded sub-identifiers are
+/// rejected too, as X.690 8.19.2 requires.
+pub(crate) fn decode_oid<D: Decoder>(decoder: &mut D) -> Result<ObjectIdentifier, D::Error> {
+    let bytes = decoder.decode_octet_string(Tag::OBJECT_IDENTIFIER)?;
+    parse_subidentifiers(&bytes).map_err(D::Error::custom)
+}
+
+fn parse_subidentifiers(bytes: &[u8]) -> Result<ObjectIdentifier, String> {
+    let mut arcs = vec![];
+    let mut arc: u32 = 0;
+    let mut start = true;
+    for (i, b) in bytes.iter().enumerate() {
+        // The first sub-identifier holds two arcs.
+        if arcs.len() == MAX_OID_LEN - 1 {
+            return Err(too_long());
+        }
+        if start && *b == 0x80 {
+            return Err(format!("OID sub-identifier at byte {} is padded", i));
+        }
+        start = b & 0x80 == 0;
+        arc = arc
+            .checked_mul(128)
+            .map(|arc| arc | u32::from(b & 0x7f))
+            .ok_or_else(|| format!("OID sub-identifier {} does not fit in 32 bits", arcs.len()))?;
+
+        if b & 0x80 == 0 {
+            arcs.push(arc);
+            arc = 0;
+        } else if i == bytes.len() - 1 {
+            return Err("OID ends in a truncated sub-identifier".to_owned());
+        }
+    }
+
+    let first = match arcs.first() {
+        Some(first) => *first,
+        None => return Err("OID is empty".to_owned()),
+    };
+    let (a, b) = match first {
+        0..=39 => (0, first),
+        40..=79 => (1, first - 40),
+        _ => (2, first - 80),
+    };
+    arcs.splice(0..1, vec![a, b]);
+
+    ObjectIdentifier::new(arcs).ok_or_else(|| format!("Unsupported OID root arc {}", a))
+}
+
+fn too_long() -> String {
+    format!("OID has more than {} sub-identifiers", MAX_OID_LEN)
+}
+
+/// The most sub-identifiers an OID may have in SNMP (RFC 2578 3.5).
+pub const MAX_OID_LEN: usize = 128;
+
+/// Arithmetic on OIDs, used to move between table columns and row indices.
+pub trait ObjectIdentifierExt {
+    /// Returns a new OID with `arcs` appended, e.g. `ifDescr` + `[3]`.
+    fn child(&self, arcs: &[u32]) -> ObjectIdentifier;
+
+    /// Returns the arcs following `root`, or `None` if `root` is not a prefix.
+    fn strip_prefix(&self, root: &ObjectIdentifier) -> Option<Vec<u32>>;
+
+    /// Builds an OID, rejecting what `ObjectIdentifier::new` would accept but
+    /// SNMP does not: more than `MAX_OID_LEN` arcs.
+    fn from_arcs(arcs: Vec<u32>) -> Result<ObjectIdentifier, String>
+    where
+        Self: Sized;
+
+    /// Decodes the contents of a BER-encoded OID, without the tag and length:
+    /// `2b 06 01` is `1.3.6.1`. Sub-identifiers must be in their shortest
+    /// form, as X.690 8.19.2 requires.
+    fn from_encoded(bytes: &[u8]) -> Result<ObjectIdentifier, String>
+    where
+        Self: Sized;
+}
+
+impl ObjectIdentifierExt for ObjectIdentifier {
+    fn child(&self, arcs: &[u32]) -> ObjectIdentifier {
+        let mut oid = self.clone();
+        oid.extend_from_slice(arcs);
+        oid
+    }
+
+    fn strip_prefix(&self, root: &ObjectIdentifier) -> Option<Vec<u32>> {
+        self.as_slice()
+            .strip_prefix(root.as_slice())
+            .map(|s| s.to_vec())
+    }
+
+    fn from_arcs(arcs: Vec<u32>) -> Result<ObjectIdentifier, String> {
+        if arcs.len() > MAX_OID_LEN {
+            return Err(too_long());
+        }
+
+        ObjectIdentifier::new(arcs)
+            .ok_or_else(|| "OID needs at least 2 arcs, starting with 0 or 1".to_owned())
+    }
+
+    fn from_encoded(bytes: &[u8]) -> Result<ObjectIdentifier, String> {
+        parse_subidentifiers(bytes)
+    }
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn parse_oid(s: &str) -> Option<ObjectIdentifier> {
+    let subids = s
+        .trim_start_matches('.')
+        .split('.')
+        .map(|subid| subid.parse::<u32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    ObjectIdentifier::from_arcs(subids).ok()
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct TimeTicks(u32);
 
 impl TimeTicks {
-    fn new(ticks: u32) -> TimeTicks {
-        TimeTicks { 0: ticks }
+    pub fn new(ticks: u32) -> TimeTicks {
+        TimeTicks(ticks)
+    }
+
+    pub fn ticks(&self) -> u32 {
+        self.0
     }
 }
 
@@ -57,7 +224,7 @@ impl Decode for TimeTicks {
 
 impl From<time::Duration> for TimeTicks {
     fn from(d: time::Duration) -> TimeTicks {
-        TimeTicks{ 0: (d.as_millis() / 10) as u32 }
+        TimeTicks((d.as_millis() / 10) as u32)
     }
 }
 
@@ -77,6 +244,21 @@ impl fmt::Display for TimeTicks {
     }
 }
 
+/// The SNMPv2-TC `DateAndTime` textual convention.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DateAndTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub deci_seconds: u8,
+    /// Direction (`'+'` or `'-'`), hours and minutes from UTC, if the agent
+    /// sent them.
+    pub utc_offset: Option<(char, u8, u8)>,
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub enum Value {
     Null,
@@ -92,10 +274,278 @@ pub enum Value {
     NoSuchObject,
     NoSuchInstance,
     EndOfMIBView,
+    /// An application- or private-class value of a type this crate does not
+    /// know, such as a vendor extension, with its raw contents. Encoding one
+    /// with a tag of any other class, or of a type the crate knows, fails.
+    Unknown {
+        tag: Tag,
+        bytes: OctetString,
+    },
+}
+
+impl Value {
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            Value::Integer(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_oid(&self) -> Option<&ObjectIdentifier> {
+        match self {
+            Value::Oid(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_timeticks(&self) -> Option<&TimeTicks> {
+        match self {
+            Value::Timeticks(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::OctetStr(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the contents of an `OctetStr`, replacing invalid UTF-8.
+    pub fn as_string(&self) -> Option<String> {
+        self.as_bytes()
+            .map(|v| String::from_utf8_lossy(v).into_owned())
+    }
+
+    /// A `DisplayString`, rejecting text the textual convention does not allow
+    /// rather than leaving the agent to refuse it.
+    pub fn display_string(s: &str) -> Result<Value, SnmpError> {
+        check_display_string(s.as_bytes()).map_err(SnmpError::InvalidDisplayString)?;
+        Ok(Value::OctetStr(OctetString::copy_from_slice(s.as_bytes())))
+    }
+
+    /// Whether this is an `OctetStr` that is a valid `DisplayString`.
+    pub fn is_valid_display_string(&self) -> bool {
+        self.as_bytes()
+            .is_some_and(|v| check_display_string(v).is_ok())
+    }
+
+    /// Whether this is an `OctetStr` with one of the `allowed` lengths.
+    pub fn octets_len_in(&self, allowed: &[usize]) -> bool {
+        self.as_bytes().is_some_and(|v| allowed.contains(&v.len()))
+    }
+
+    /// Reads a `MacAddress`, which is exactly 6 octets.
+    pub fn as_mac(&self) -> Option<[u8; 6]> {
+        if !self.octets_len_in(&[6]) {
+            return None;
+        }
+
+        let mut mac = [0; 6];
+        mac.copy_from_slice(self.as_bytes()?);
+        Some(mac)
+    }
+
+    /// Reads an `InetAddressIPv6`, which is exactly 16 octets.
+    pub fn as_ipv6(&self) -> Option<Ipv6Addr> {
+        if !self.octets_len_in(&[16]) {
+            return None;
+        }
+
+        let mut octets = [0; 16];
+        octets.copy_from_slice(self.as_bytes()?);
+        Some(Ipv6Addr::from(octets))
+    }
+
+    /// Reads a `DateAndTime`, which is 8 octets, or 11 with a UTC offset.
+    pub fn as_datetime(&self) -> Option<DateAndTime> {
+        if !self.octets_len_in(&[8, 11]) {
+            return None;
+        }
+
+        let b = self.as_bytes()?;
+        let utc_offset = match b.get(8..) {
+            Some([direction, hours, minutes]) if *direction == b'+' || *direction == b'-' => {
+                Some((*direction as char, *hours, *minutes))
+            }
+            Some([]) => None,
+            _ => return None,
+        };
+
+        Some(DateAndTime {
+            year: u16::from_be_bytes([b[0], b[1]]),
+            month: b[2],
+            day: b[3],
+            hour: b[4],
+            minutes: b[5],
+            seconds: b[6],
+            deci_seconds: b[7],
+            utc_offset,
+        })
+    }
+
+    /// Reads a `BITS` value: the positions of the bits set in an `OctetStr`,
+    /// in ascending order. Bit 0 is the most significant bit of the first
+    /// octet (RFC 2578 7.1.4).
+    pub fn as_bits(&self) -> Option<Vec<usize>> {
+        let bytes = self.as_bytes()?;
+        let bits = (0..bytes.len() * 8)
+            .filter(|bit| bytes[bit / 8] & (0x80 >> (bit % 8)) != 0)
+            .collect();
+        Some(bits)
+    }
+
+    /// Writes a `BITS` value as its set bits, named from `names` as in
+    /// `up(0) testing(2)`. Bits without a name are written as their number.
+    pub fn format_bits(&self, names: &[(usize, &str)]) -> Option<String> {
+        let bits: Vec<_> = self
+            .as_bits()?
+            .into_iter()
+            .map(|bit| match names.iter().find(|(named, _)| *named == bit) {
+                Some((_, name)) => format!("{}({})", name, bit),
+                None => bit.to_string(),
+            })
+            .collect();
+        Some(bits.join(" "))
+    }
+
+    /// Wraps a Counter64 in an `Opaque`, the way agents send it to SNMPv1
+    /// managers.
+    pub fn opaque_counter64(v: u64) -> Value {
+        let bytes = v.to_be_bytes();
+        let zeros = bytes.iter().take_while(|b| **b == 0).count().min(7);
+        let mut content = bytes[zeros..].to_vec();
+        if content[0] & 0x80 != 0 {
+            content.insert(0, 0);
+        }
+
+        let mut opaque = OPAQUE_COUNTER64.to_vec();
+        opaque.push(content.len() as u8);
+        opaque.extend(content);
+        Value::Opaque(OctetString::from(opaque))
+    }
+
+    /// Reads a `Counter64`, or a Counter64 wrapped in an `Opaque`.
+    pub fn as_counter64(&self) -> Option<u64> {
+        match self {
+            Value::Counter64(v) => Some(*v),
+            Value::Opaque(v) => opaque_counter64(v),
+            _ => None,
+        }
+    }
+
+    /// `Counter32` and `Counter64` only ever increase, wrapping at their maximum.
+    /// A Counter64 wrapped in an `Opaque` counts as well.
+    pub fn is_counter(&self) -> bool {
+        matches!(self, Value::Counter32(_)) || self.as_counter64().is_some()
+    }
+
+    /// The SNMPv2 exceptions an agent answers with in place of a value.
+    pub fn is_exception(&self) -> bool {
+        matches!(
+            self,
+            Value::NoSuchObject | Value::NoSuchInstance | Value::EndOfMIBView
+        )
+    }
+
+    /// `Gauge32` moves both ways and latches at its maximum instead of wrapping.
+    pub fn is_gauge(&self) -> bool {
+        matches!(self, Value::Gauge32(_))
+    }
+
+    /// The increase of a counter since `previous`, allowing for one wrap.
+    ///
+    /// Returns `None` unless both values are counters of the same width. A
+    /// `Gauge32` is rejected as well: a drop in a gauge is a real decrease,
+    /// and reading it as a wrap would report a huge bogus delta.
+    pub fn counter_delta(&self, previous: &Value) -> Option<u64> {
+        match (previous, self) {
+            (Value::Counter32(a), Value::Counter32(b)) => Some(b.wrapping_sub(*a) as u64),
+            _ => Some(self.as_counter64()?.wrapping_sub(previous.as_counter64()?)),
+        }
+    }
+}
+
+/// Checks `bytes` against the `DisplayString` textual convention (RFC 2579):
+/// at most 255 characters of NVT ASCII, where CR is only followed by LF or NUL.
+fn check_display_string(bytes: &[u8]) -> Result<(), String> {
+    if bytes.len() > 255 {
+        return Err(format!(
+            "{} characters exceeds the limit of 255",
+            bytes.len()
+        ));
+    }
+
+    for (i, b) in bytes.iter().enumerate() {
+        let allowed = match b {
+            0x20..=0x7e | 0 | 0x07..=0x0c => true,
+            b'\r' => matches!(bytes.get(i + 1), Some(b'\n') | Some(0)),
+            _ => false,
+        };
+        if !allowed {
+            return Err(format!("byte {:#04x} at {} is not NVT ASCII", b, i));
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the Counter64 in the contents of an `Opaque`, if that is what it holds.
+fn opaque_counter64(bytes: &[u8]) -> Option<u64> {
+    let content = match bytes {
+        [0x9f, 0x76, len, content @ ..] if usize::from(*len) == content.len() => content,
+        _ => return None,
+    };
+    let content = match content {
+        [0, rest @ ..] if rest.len() == 8 => rest,
+        _ if (1..=8).contains(&content.len()) => content,
+        _ => return None,
+    };
+
+    Some(content.iter().fold(0, |v, b| v << 8 | u64::from(*b)))
+}
+
+// There is deliberately no `From<u32>`: it could be a Gauge32, Counter32 or
+// Timeticks, so those are spelled out with their variant.
+impl From<i32> for Value {
+    fn from(v: i32) -> Value {
+        Value::Integer(v)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Value {
+        Value::OctetStr(OctetString::copy_from_slice(v.as_bytes()))
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Value {
+        Value::OctetStr(OctetString::from(v.into_bytes()))
+    }
+}
+
+impl From<&[u8]> for Value {
+    fn from(v: &[u8]) -> Value {
+        Value::OctetStr(OctetString::copy_from_slice(v))
+    }
+}
+
+impl From<Ipv4Addr> for Value {
+    fn from(v: Ipv4Addr) -> Value {
+        Value::IpAddr(v)
+    }
+}
+
+impl From<ObjectIdentifier> for Value {
+    fn from(v: ObjectIdentifier) -> Value {
+        Value::Oid(v)
+    }
 }
 
 fn ip_to_bytes(addr: &Ipv4Addr) -> OctetString {
-    OctetString::copy_from_slice(&u32::from_ne_bytes(addr.octets()).to_be_bytes())
+    OctetString::copy_from_slice(&addr.octets())
 }
 
 fn bytes_to_ip(bytes: &[u8]) -> Ipv4Addr {
@@ -110,7 +560,7 @@ impl Encode for Value {
     fn encode_with_tag<E: Encoder>(&self, encoder: &mut E, _: Tag) -> Result<(), E::Error> {
         match self {
             Value::Null => ().encode(encoder),
-            Value::Oid(v) => v.encode(encoder),
+            Value::Oid(v) => encode_oid(encoder, v),
             Value::Integer(v) => v.encode(encoder),
             Value::IpAddr(addr) => ip_to_bytes(addr).encode_with_tag(encoder, TAG_IPADDR),
             Value::Gauge32(v) => v.encode_with_tag(encoder, TAG_GAUGE32),
@@ -122,6 +572,17 @@ impl Encode for Value {
             Value::NoSuchObject => ().encode_with_tag(encoder, TAG_NOSUCHOBJECT),
             Value::NoSuchInstance => ().encode_with_tag(encoder, TAG_NOSUCHINSTANCE),
             Value::EndOfMIBView => ().encode_with_tag(encoder, TAG_ENDOFMIBVIEW),
+            Value::Unknown { tag, bytes } => {
+                if !matches!(tag.class, Class::Application | Class::Private)
+                    || KNOWN_APPLICATION_TAGS.contains(tag)
+                {
+                    return Err(<E::Error as rasn::enc::Error>::custom(format!(
+                        "Unknown value with tag {:?}, which is not of an unknown type",
+                        tag
+                    )));
+                }
+                bytes.encode_with_tag(encoder, *tag)
+            }
         }
     }
 }
@@ -131,7 +592,7 @@ impl Decode for Value {
         let tag = decoder.peek_tag()?;
         let value = match tag {
             Tag::NULL => <()>::decode(decoder).map(|_| Value::Null)?,
-            Tag::OBJECT_IDENTIFIER => Value::Oid(ObjectIdentifier::decode(decoder)?),
+            Tag::OBJECT_IDENTIFIER => Value::Oid(decode_oid(decoder)?),
             Tag::INTEGER => Value::Integer(i32::decode(decoder)?),
             TAG_IPADDR => {
                 let bytes = decoder.decode_octet_string(TAG_IPADDR)?;
@@ -150,14 +611,18 @@ impl Decode for Value {
             Tag::OCTET_STRING => Value::OctetStr(OctetString::decode(decoder)?),
             TAG_OPAQUE => Value::Opaque(OctetString::decode_with_tag(decoder, TAG_OPAQUE)?),
             TAG_NOSUCHOBJECT => {
-                <()>::decode_with_tag(decoder, TAG_NOSUCHOBJECT).map(|_| Value::Null)?
+                <()>::decode_with_tag(decoder, TAG_NOSUCHOBJECT).map(|_| Value::NoSuchObject)?
             }
             TAG_NOSUCHINSTANCE => {
-                <()>::decode_with_tag(decoder, TAG_NOSUCHINSTANCE).map(|_| Value::Null)?
+                <()>::decode_with_tag(decoder, TAG_NOSUCHINSTANCE).map(|_| Value::NoSuchInstance)?
             }
             TAG_ENDOFMIBVIEW => {
-                <()>::decode_with_tag(decoder, TAG_ENDOFMIBVIEW).map(|_| Value::Null)?
+                <()>::decode_with_tag(decoder, TAG_ENDOFMIBVIEW).map(|_| Value::EndOfMIBView)?
             }
+            _ if matches!(tag.class, Class::Application | Class::Private) => Value::Unknown {
+                tag,
+                bytes: OctetString::from(decoder.decode_octet_string(tag)?),
+            },
             _ => {
                 return Err(D::Error::custom(format!(
                     "Unexpected tag {:?}, expected Value",
@@ -170,6 +635,8 @@ impl Decode for Value {
     }
 }
 
+/// The alternate form (`{:#}`) displays octet strings that are not valid UTF-8
+/// lossily, replacing the invalid bytes with U+FFFD instead of the whole string.
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -181,18 +648,22 @@ impl fmt::Display for Value {
             Value::Counter32(v) => write!(f, "{}", v),
             Value::Counter64(v) => write!(f, "{}", v),
             Value::Timeticks(v) => write!(f, "{}", v),
+            Value::OctetStr(v) if f.alternate() => write!(f, "{}", String::from_utf8_lossy(v)),
             Value::OctetStr(v) => write!(f, "{}", std::str::from_utf8(v).unwrap_or("<Invalid UTF8>")),
             Value::Opaque(v) => write!(f, "<Opaque: {} bytes>", v.len()),
             Value::NoSuchObject => write!(f, "<NoSuchObject"),
             Value::NoSuchInstance => write!(f, "<NoSuchInstance>"),
             Value::EndOfMIBView => write!(f, "<EndOfMIBView>"),
+            Value::Unknown { tag, bytes } => {
+                write!(f, "<{:?} {}: {} bytes>", tag.class, tag.value, bytes.len())
+            }
         }
     }
 }
 
 pub type SnmpString = Implicit<OctetString, Utf8String>;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Version {
     V1,
     V2C,
@@ -208,7 +679,7 @@ impl Encode for Version {
         match self {
             Version::V1 => 0.encode_with_tag(encoder, tag),
             Version::V2C => 1.encode_with_tag(encoder, tag),
-            Version::V3 => 2.encode_with_tag(encoder, tag),
+            Version::V3 => 3.encode_with_tag(encoder, tag),
         }
     }
 }
@@ -219,10 +690,10 @@ impl Decode for Version {
         let result = match version {
             0 => Version::V1,
             1 => Version::V2C,
-            2 => Version::V3,
+            3 => Version::V3,
             _ => {
                 return Err(D::Error::custom(format!(
-                    "Unexpected version {:?}, expected 0-2",
+                    "Unexpected version {:?}, expected 0, 1 or 3",
                     version
                 )))
             }
@@ -232,6 +703,35 @@ impl Decode for Version {
     }
 }
 
+/// Accepts `1`, `2c` and `3`, with or without a leading `v`, in any case.
+impl FromStr for Version {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Version, String> {
+        let lower = s.to_ascii_lowercase();
+        match lower.strip_prefix('v').unwrap_or(&lower) {
+            "1" => Ok(Version::V1),
+            "2c" => Ok(Version::V2C),
+            "3" => Ok(Version::V3),
+            _ => Err(format!(
+                "Unknown SNMP version {:?}, expected v1, v2c or v3",
+                s
+            )),
+        }
+    }
+}
+
+/// Writes `v1`, `v2c` or `v3`.
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Version::V1 => write!(f, "v1"),
+            Version::V2C => write!(f, "v2c"),
+            Version::V3 => write!(f, "v3"),
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! oid {
     (
@@ -257,6 +757,20 @@ impl VarBinding {
             value: Value::Null,
         }
     }
+
+    /// The dotted OID and the value.
+    pub fn to_pair(&self) -> (String, Value) {
+        (
+            ObjectIdentifierDisplayWrapper(&self.name).to_string(),
+            self.value.clone(),
+        )
+    }
+}
+
+impl From<VarBinding> for (ObjectIdentifier, Value) {
+    fn from(vb: VarBinding) -> (ObjectIdentifier, Value) {
+        (vb.name, vb.value)
+    }
 }
 
 impl AsnType for VarBinding {
@@ -265,7 +779,7 @@ impl AsnType for VarBinding {
 impl Encode for VarBinding {
     fn encode_with_tag<E: Encoder>(&self, encoder: &mut E, tag: Tag) -> Result<(), E::Error> {
         encoder.encode_sequence(tag, |sequence| {
-            self.name.encode(sequence)?;
+            encode_oid(sequence, &self.name)?;
             self.value.encode(sequence)?;
             Ok(())
         })?;
@@ -277,7 +791,7 @@ impl Encode for VarBinding {
 impl Decode for VarBinding {
     fn decode_with_tag<D: Decoder>(decoder: &mut D, _: Tag) -> Result<Self, D::Error> {
         let mut seq = decoder.decode_sequence(Self::TAG)?;
-        let name = ObjectIdentifier::decode(&mut seq)?;
+        let name = decode_oid(&mut seq)?;
         let value = Value::decode(&mut seq)?;
 
         Ok(VarBinding { name, value })
@@ -295,6 +809,147 @@ impl fmt::Display for VarBinding {
     }
 }
 
+/// How `Value::display` and `VarBinding::display` write values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DisplayStyle {
+    /// The bare value, as `Display` writes it.
+    Plain,
+    /// The value behind its SMI type, as net-snmp's tools print it with `-On`:
+    /// `Counter32: 42`, `STRING: "foo"`, `.1.3.6.1.2.1.1.5.0 = STRING: "foo"`.
+    NetSnmp,
+}
+
+impl Value {
+    pub fn display(&self, style: DisplayStyle) -> ValueDisplay<'_> {
+        ValueDisplay { value: self, style }
+    }
+}
+
+impl VarBinding {
+    pub fn display(&self, style: DisplayStyle) -> VarBindingDisplay<'_> {
+        VarBindingDisplay {
+            binding: self,
+            style,
+        }
+    }
+}
+
+/// A `Value` written in a `DisplayStyle`.
+pub struct ValueDisplay<'a> {
+    value: &'a Value,
+    style: DisplayStyle,
+}
+
+impl<'a> fmt::Display for ValueDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.style == DisplayStyle::Plain {
+            return fmt::Display::fmt(self.value, f);
+        }
+
+        match self.value {
+            Value::Null => write!(f, "NULL"),
+            Value::Oid(v) => write!(f, "OID: .{}", ObjectIdentifierDisplayWrapper(v)),
+            Value::Integer(v) => write!(f, "INTEGER: {}", v),
+            Value::IpAddr(v) => write!(f, "IpAddress: {}", v),
+            Value::Gauge32(v) => write!(f, "Gauge32: {}", v),
+            Value::Counter32(v) => write!(f, "Counter32: {}", v),
+            Value::Counter64(v) => write!(f, "Counter64: {}", v),
+            Value::Timeticks(v) => write_net_snmp_ticks(f, v.ticks()),
+            Value::OctetStr(v) => match std::str::from_utf8(v) {
+                Ok(s) if s.chars().all(|c| !c.is_control() || c.is_whitespace()) => {
+                    write!(f, "STRING: {}", QuotedDisplay(s))
+                }
+                _ => write!(f, "Hex-STRING: {}", HexDisplay(v)),
+            },
+            Value::Opaque(v) => match self.value.as_counter64() {
+                Some(counter) => write!(f, "Opaque: Counter64: {}", counter),
+                None => write!(f, "OPAQUE: {}", HexDisplay(v)),
+            },
+            Value::NoSuchObject => write!(f, "No Such Object available on this agent at this OID"),
+            Value::NoSuchInstance => write!(f, "No Such Instance currently exists at this OID"),
+            Value::EndOfMIBView => write!(
+                f,
+                "No more variables left in this MIB View (It is past the end of the MIB tree)"
+            ),
+            Value::Unknown { bytes, .. } => {
+                write!(f, "Wrong Type: Hex-STRING: {}", HexDisplay(bytes))
+            }
+        }
+    }
+}
+
+/// Writes `ticks` the way net-snmp does: `Timeticks: (12345) 0:02:03.45`,
+/// with the days, if any, as `1 day, ` or `2 days, ` before the time.
+fn write_net_snmp_ticks(f: &mut fmt::Formatter<'_>, ticks: u32) -> fmt::Result {
+    let (hundredths, secs) = (ticks % 100, ticks / 100);
+    let (days, hours) = (secs / 86400, secs / 3600 % 24);
+    let (minutes, seconds) = (secs / 60 % 60, secs % 60);
+
+    write!(f, "Timeticks: ({}) ", ticks)?;
+    match days {
+        0 => {}
+        1 => write!(f, "1 day, ")?,
+        _ => write!(f, "{} days, ", days)?,
+    }
+    write!(
+        f,
+        "{}:{:02}:{:02}.{:02}",
+        hours, minutes, seconds, hundredths
+    )
+}
+
+/// Octets as space-separated upper-case hex pairs.
+struct HexDisplay<'a>(&'a [u8]);
+
+impl<'a> fmt::Display for HexDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, b) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{:02X}", b)?;
+        }
+        Ok(())
+    }
+}
+
+/// Text in double quotes the way net-snmp writes a STRING: only `"` and `\`
+/// are escaped, and newlines and tabs are written as they are.
+struct QuotedDisplay<'a>(&'a str);
+
+impl<'a> fmt::Display for QuotedDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"")?;
+        for c in self.0.chars() {
+            if c == '"' || c == '\\' {
+                write!(f, "\\")?;
+            }
+            write!(f, "{}", c)?;
+        }
+        write!(f, "\"")
+    }
+}
+
+/// A `VarBinding` written in a `DisplayStyle`.
+pub struct VarBindingDisplay<'a> {
+    binding: &'a VarBinding,
+    style: DisplayStyle,
+}
+
+impl<'a> fmt::Display for VarBindingDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.style {
+            DisplayStyle::Plain => fmt::Display::fmt(self.binding, f),
+            DisplayStyle::NetSnmp => write!(
+                f,
+                ".{} = {}",
+                ObjectIdentifierDisplayWrapper(&self.binding.name),
+                self.binding.value.display(self.style)
+            ),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -323,6 +978,22 @@ mod tests {
         assert_eq!(d, v);
     }
 
+    #[test]
+    fn high_unsigned_values_round_trip() {
+        let cases: &[(Value, &[u8])] = &[
+            (Value::Gauge32(u32::MAX), &[66, 5, 0, 255, 255, 255, 255]),
+            (Value::Counter32(0x8000_0000), &[65, 5, 0, 128, 0, 0, 0]),
+            (
+                Value::Counter64(u64::MAX),
+                &[70, 9, 0, 255, 255, 255, 255, 255, 255, 255, 255],
+            ),
+        ];
+        for (v, b) in cases {
+            assert_eq!(&encode(v).unwrap(), b);
+            assert_eq!(&decode::<Value>(b).unwrap(), v);
+        }
+    }
+
     #[test]
     fn encode_integer() {
         let v = Value::Integer(5);
@@ -332,7 +1003,7 @@ mod tests {
         // Ip Address
         let v = Value::IpAddr(Ipv4Addr::new(10, 0, 0, 1));
         let r = encode(&v).expect("Can encode nulls");
-        assert_eq!(r, &[64, 4, 1, 0, 0, 10]);
+        assert_eq!(r, &[64, 4, 10, 0, 0, 1]);
 
         // Gauge32
         let v = Value::Gauge32(128);
@@ -380,6 +1051,252 @@ mod tests {
         assert_eq!(r, &[130, 0]);
     }
 
+    #[test]
+    fn encode_negative_integer() {
+        let cases: &[(i32, &[u8])] = &[
+            (-1, &[2, 1, 0xff]),
+            (-128, &[2, 1, 0x80]),
+            (-129, &[2, 2, 0xff, 0x7f]),
+            (i32::MIN, &[2, 4, 0x80, 0, 0, 0]),
+        ];
+
+        for (i, b) in cases {
+            let v = Value::Integer(*i);
+            let e = encode(&v).unwrap();
+            assert_eq!(&e, b);
+
+            let d = decode::<Value>(b).unwrap();
+            assert_eq!(d, v);
+        }
+    }
+
+    #[test]
+    fn encode_multibyte_oid() {
+        let v = Value::Oid(ObjectIdentifier::new(vec![1, 3, 6, 1, 4, 1, 8072, 840]).unwrap());
+        let b: &[u8] = &[6, 9, 43, 6, 1, 4, 1, 0xbf, 0x08, 0x86, 0x48];
+        let e = encode(&v).unwrap();
+        assert_eq!(e, b);
+
+        let d = decode::<Value>(b).unwrap();
+        assert_eq!(d, v);
+    }
+
+    #[test]
+    fn decode_oid_arc_overflow() {
+        let max: &[u8] = &[48, 12, 6, 8, 43, 6, 1, 0x8f, 0xff, 0xff, 0xff, 0x7f, 5, 0];
+        let d = decode::<VarBinding>(max).unwrap();
+        assert_eq!(d.name.as_slice(), &[1, 3, 6, 1, u32::MAX]);
+
+        let overflow: &[u8] = &[48, 12, 6, 8, 43, 6, 1, 0x90, 0x80, 0x80, 0x80, 0x00, 5, 0];
+        assert!(decode::<VarBinding>(overflow).is_err());
+
+        let truncated: &[u8] = &[6, 3, 43, 6, 0x81];
+        assert!(decode::<Value>(truncated).is_err());
+
+        let padded: &[u8] = &[6, 3, 43, 0x80, 6];
+        match decode::<Value>(padded) {
+            Err(e) => assert!(e.to_string().contains("is padded"), "{}", e),
+            r => panic!("Unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn oid_length_limit() {
+        let arcs = |n: usize| {
+            let mut arcs = vec![1; n];
+            arcs[1] = 3;
+            arcs
+        };
+        assert_eq!(ObjectIdentifier::from_arcs(arcs(128)).unwrap().len(), 128);
+        assert_eq!(
+            ObjectIdentifier::from_arcs(arcs(129)),
+            Err("OID has more than 128 sub-identifiers".to_owned())
+        );
+        assert!(ObjectIdentifier::from_arcs(vec![1]).is_err());
+
+        // 43 encodes the first two arcs, 1.3.
+        let mut b = vec![6, 127, 43];
+        b.extend(vec![1; 126]);
+        let longest = ObjectIdentifier::from_arcs(arcs(128)).unwrap();
+        assert_eq!(decode::<Value>(&b).unwrap(), Value::Oid(longest));
+
+        let mut b = vec![6, 0x81, 128, 43];
+        b.extend(vec![1; 127]);
+        match decode::<Value>(&b) {
+            Err(e) => assert!(e.to_string().contains("more than 128"), "{}", e),
+            r => panic!("Unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn oid_from_encoded_subidentifiers() {
+        let decoded = ObjectIdentifier::from_encoded(&[0x2b, 0x06, 0x01]).unwrap();
+        assert_eq!(decoded, oid! {1,3,6,1});
+        // Arcs past 127 span several bytes.
+        let b = [0x2b, 6, 1, 4, 1, 0x81, 0x8a, 0x2c];
+        let decoded = ObjectIdentifier::from_encoded(&b).unwrap();
+        assert_eq!(decoded, oid! {1,3,6,1,4,1,17708});
+        assert_eq!(
+            ObjectIdentifier::from_encoded(&[0x2b, 6, 1, 0x81]),
+            Err("OID ends in a truncated sub-identifier".to_owned())
+        );
+        assert_eq!(
+            ObjectIdentifier::from_encoded(&[0x2b, 0x80, 6]),
+            Err("OID sub-identifier at byte 1 is padded".to_owned())
+        );
+        assert!(ObjectIdentifier::from_encoded(&[]).is_err());
+        assert!(ObjectIdentifier::from_encoded(&[0x2b, 0x90, 0x80, 0x80, 0x80, 0]).is_err());
+    }
+
+    #[test]
+    fn version_spellings() {
+        let spellings = [
+            ("1", Version::V1),
+            ("v1", Version::V1),
+            ("V1", Version::V1),
+            ("2c", Version::V2C),
+            ("v2c", Version::V2C),
+            ("V2C", Version::V2C),
+            ("3", Version::V3),
+            ("v3", Version::V3),
+            ("V3", Version::V3),
+        ];
+        for (s, version) in spellings.iter() {
+            assert_eq!(s.parse::<Version>(), Ok(*version), "{}", s);
+            assert_eq!(version.to_string().parse::<Version>(), Ok(*version));
+        }
+        assert_eq!(Version::V2C.to_string(), "v2c");
+
+        assert_eq!(
+            "v4".parse::<Version>(),
+            Err("Unknown SNMP version \"v4\", expected v1, v2c or v3".to_owned())
+        );
+        assert!("2".parse::<Version>().is_err());
+        assert!("".parse::<Version>().is_err());
+    }
+
+    #[test]
+    fn display_styles() {
+        let cases = [
+            (Value::Counter32(42), "42", "Counter32: 42"),
+            (Value::Gauge32(7), "7", "Gauge32: 7"),
+            (Value::Integer(-3), "-3", "INTEGER: -3"),
+            (
+                Value::Timeticks(TimeTicks::new(12345)),
+                "(12345) 0d 0:02:03.45",
+                "Timeticks: (12345) 0:02:03.45",
+            ),
+            (
+                Value::Timeticks(TimeTicks::new(17_280_000)),
+                "(17280000) 2d 0:00:00.00",
+                "Timeticks: (17280000) 2 days, 0:00:00.00",
+            ),
+            (Value::from("foo"), "foo", "STRING: \"foo\""),
+            (
+                Value::from("line 1\nsaid \"hi\" C:\\"),
+                "line 1\nsaid \"hi\" C:\\",
+                "STRING: \"line 1\nsaid \\\"hi\\\" C:\\\\\"",
+            ),
+            (
+                Value::OctetStr(OctetString::from_static(&[0, 0x1a, 0xff])),
+                "<Invalid UTF8>",
+                "Hex-STRING: 00 1A FF",
+            ),
+            (
+                Value::Oid(oid! {1,3,6,1,4,1,9}),
+                "1.3.6.1.4.1.9",
+                "OID: .1.3.6.1.4.1.9",
+            ),
+            (
+                Value::IpAddr(Ipv4Addr::new(10, 0, 0, 1)),
+                "10.0.0.1",
+                "IpAddress: 10.0.0.1",
+            ),
+            (
+                Value::opaque_counter64(5),
+                "<Opaque: 4 bytes>",
+                "Opaque: Counter64: 5",
+            ),
+        ];
+        for (value, plain, net_snmp) in cases.iter() {
+            assert_eq!(value.display(DisplayStyle::Plain).to_string(), *plain);
+            assert_eq!(value.display(DisplayStyle::NetSnmp).to_string(), *net_snmp);
+        }
+
+        let vb = VarBinding::new(oid! {1,3,6,1,2,1,1,5,0}, Value::from("core-1"));
+        assert_eq!(
+            vb.display(DisplayStyle::NetSnmp).to_string(),
+            ".1.3.6.1.2.1.1.5.0 = STRING: \"core-1\""
+        );
+        assert_eq!(vb.display(DisplayStyle::Plain).to_string(), vb.to_string());
+    }
+
+    #[test]
+    fn bits_are_numbered_from_the_most_significant() {
+        let v = Value::from(&[0xa0][..]);
+        assert_eq!(v.as_bits(), Some(vec![0, 2]));
+        assert_eq!(Value::from(&[0x01, 0x80][..]).as_bits(), Some(vec![7, 8]));
+        assert_eq!(Value::Integer(1).as_bits(), None);
+
+        let names = [(0, "sunday"), (1, "monday")];
+        assert_eq!(v.format_bits(&names).unwrap(), "sunday(0) 2");
+        assert_eq!(Value::from(&[][..]).format_bits(&names).unwrap(), "");
+    }
+
+    #[test]
+    fn unknown_application_tag() {
+        // [APPLICATION 5], the NsapAddress of SNMPv2 drafts.
+        let v = decode::<Value>(&[0x45, 3, 1, 2, 3]).unwrap();
+        assert_eq!(
+            v,
+            Value::Unknown {
+                tag: Tag::new(Class::Application, 5),
+                bytes: OctetString::from_static(&[1, 2, 3]),
+            }
+        );
+        assert_eq!(encode(&v).unwrap(), [0x45, 3, 1, 2, 3]);
+        assert_eq!(v.to_string(), "<Application 5: 3 bytes>");
+
+        assert!(decode::<Value>(&[0x85, 0]).is_err());
+
+        // Tags of other classes or of known types would read back as other
+        // values, or not at all.
+        let taken = [
+            Tag::OCTET_STRING,
+            Tag::new(Class::Context, 0),
+            Tag::new(Class::Application, 0),
+            Tag::new(Class::Application, 1),
+            Tag::new(Class::Application, 2),
+            Tag::new(Class::Application, 3),
+            Tag::new(Class::Application, 4),
+            Tag::new(Class::Application, 6),
+        ];
+        for tag in taken {
+            let v = Value::Unknown {
+                tag,
+                bytes: OctetString::from_static(&[1]),
+            };
+            assert!(encode(&v).is_err());
+        }
+    }
+
+    #[test]
+    fn decode_exceptions() {
+        assert_eq!(decode::<Value>(&[128, 0]).unwrap(), Value::NoSuchObject);
+        assert_eq!(decode::<Value>(&[129, 0]).unwrap(), Value::NoSuchInstance);
+        assert_eq!(decode::<Value>(&[130, 0]).unwrap(), Value::EndOfMIBView);
+    }
+
+    #[test]
+    fn display_invalid_utf8() {
+        let v = Value::OctetStr(OctetString::copy_from_slice(b"Linux\xff box"));
+        assert_eq!(format!("{}", v), "<Invalid UTF8>");
+        assert_eq!(format!("{:#}", v), "Linux\u{fffd} box");
+
+        let v = Value::OctetStr(OctetString::copy_from_slice(b"Linux box"));
+        assert_eq!(format!("{:#}", v), "Linux box");
+    }
+
     #[test]
     fn encode_version() {
         let v = Version::V2C;
@@ -394,6 +1311,25 @@ mod tests {
         assert_eq!(r, &[67, 1, 12])
     }
 
+    #[test]
+    fn oid_child() {
+        let if_descr = ObjectIdentifier::new(vec![1, 3, 6, 1, 2, 1, 2, 2, 1, 2]).unwrap();
+        let oid = if_descr.child(&[3, 10]);
+        assert_eq!(oid.as_slice(), &[1, 3, 6, 1, 2, 1, 2, 2, 1, 2, 3, 10]);
+        assert_eq!(oid.strip_prefix(&if_descr), Some(vec![3, 10]));
+        assert_eq!(if_descr.strip_prefix(&if_descr), Some(vec![]));
+    }
+
+    #[test]
+    fn oid_strip_non_matching_prefix() {
+        let oid = ObjectIdentifier::new(vec![1, 3, 6, 1, 2, 1, 2, 2, 1, 2, 3]).unwrap();
+        let if_type = ObjectIdentifier::new(vec![1, 3, 6, 1, 2, 1, 2, 2, 1, 3]).unwrap();
+        assert_eq!(oid.strip_prefix(&if_type), None);
+
+        let longer = oid.child(&[1]);
+        assert_eq!(oid.strip_prefix(&longer), None);
+    }
+
     #[test]
     fn encode_binding() {
         let v = VarBinding {
@@ -403,4 +1339,191 @@ mod tests {
         let r = encode(&v).expect("Can encode");
         assert_eq!(r, &[48, 7, 6, 3, 42, 3, 4, 5, 0])
     }
+
+    #[test]
+    fn value_from() {
+        assert_eq!(Value::from(-5), Value::Integer(-5));
+        assert_eq!(
+            Value::from("eth0"),
+            Value::OctetStr(OctetString::from_static(b"eth0"))
+        );
+        assert_eq!(
+            Value::from(String::from("eth0")),
+            Value::OctetStr(OctetString::from_static(b"eth0"))
+        );
+        assert_eq!(
+            Value::from(&[0u8, 0xff][..]),
+            Value::OctetStr(OctetString::from_static(&[0, 0xff]))
+        );
+        assert_eq!(
+            Value::from(Ipv4Addr::new(10, 0, 0, 1)),
+            Value::IpAddr(Ipv4Addr::new(10, 0, 0, 1))
+        );
+
+        let oid = ObjectIdentifier::new(vec![1, 3, 6, 1]).unwrap();
+        assert_eq!(Value::from(oid.clone()), Value::Oid(oid));
+
+        let vb = VarBinding::new(ObjectIdentifier::new(vec![1, 3, 6, 1]).unwrap(), 5.into());
+        assert_eq!(vb.value, Value::Integer(5));
+    }
+
+    #[test]
+    fn display_string() {
+        let v = Value::display_string("Linux router\r\n\tline two").unwrap();
+        assert_eq!(v.as_string().unwrap(), "Linux router\r\n\tline two");
+        assert!(v.is_valid_display_string());
+
+        match Value::display_string("eth\x1b[0") {
+            Err(SnmpError::InvalidDisplayString(reason)) => {
+                assert_eq!(reason, "byte 0x1b at 3 is not NVT ASCII")
+            }
+            r => panic!("Unexpected result {:?}", r),
+        }
+        assert!(Value::display_string("a\rb").is_err());
+        assert!(Value::display_string("caf\u{e9}").is_err());
+        assert!(Value::display_string(&"x".repeat(256)).is_err());
+
+        let v = Value::OctetStr(OctetString::from_static(&[0x41, 0x7f]));
+        assert!(!v.is_valid_display_string());
+        assert!(!Value::Integer(1).is_valid_display_string());
+    }
+
+    #[test]
+    fn opaque_counter64() {
+        let v = Value::opaque_counter64(1 << 40);
+        let r = encode(&v).unwrap();
+        assert_eq!(r, &[0x44, 9, 0x9f, 0x76, 6, 1, 0, 0, 0, 0, 0]);
+
+        let v = decode::<Value>(&r).unwrap();
+        assert_eq!(v.as_counter64(), Some(1 << 40));
+        assert!(v.is_counter());
+
+        let v = Value::opaque_counter64(u64::MAX);
+        assert_eq!(v.as_bytes(), None);
+        let r = encode(&v).unwrap();
+        assert_eq!(&r[..5], &[0x44, 12, 0x9f, 0x76, 9]);
+        assert_eq!(decode::<Value>(&r).unwrap().as_counter64(), Some(u64::MAX));
+
+        let delta = Value::opaque_counter64(30).counter_delta(&Value::Counter64(10));
+        assert_eq!(delta, Some(20));
+
+        // Other Opaque contents are kept as they are.
+        let v = Value::Opaque(OctetString::from_static(&[0x9f, 0x78, 4, 0x3f, 0x80, 0, 0]));
+        let r = encode(&v).unwrap();
+        assert_eq!(decode::<Value>(&r).unwrap(), v);
+        assert_eq!(v.as_counter64(), None);
+        assert!(!v.is_counter());
+    }
+
+    #[test]
+    fn counter_delta() {
+        let delta = Value::Counter32(5).counter_delta(&Value::Counter32(u32::MAX - 4));
+        assert_eq!(delta, Some(10));
+        let delta = Value::Counter64(20).counter_delta(&Value::Counter64(8));
+        assert_eq!(delta, Some(12));
+
+        assert!(Value::Counter32(1).is_counter());
+        assert!(!Value::Gauge32(1).is_counter());
+        assert!(Value::Gauge32(1).is_gauge());
+        assert_eq!(Value::Gauge32(5).counter_delta(&Value::Gauge32(10)), None);
+        assert_eq!(Value::Counter32(5).counter_delta(&Value::Gauge32(1)), None);
+        assert_eq!(
+            Value::Counter64(5).counter_delta(&Value::Counter32(1)),
+            None
+        );
+    }
+
+    #[test]
+    fn ip_addr_network_byte_order() {
+        // Every octet distinct, so a byte swap anywhere shows up.
+        let cases: &[(Ipv4Addr, &[u8])] = &[
+            (Ipv4Addr::new(1, 2, 3, 4), &[64, 4, 1, 2, 3, 4]),
+            (Ipv4Addr::new(255, 0, 128, 1), &[64, 4, 255, 0, 128, 1]),
+        ];
+
+        for (addr, b) in cases {
+            let v = Value::IpAddr(*addr);
+            assert_eq!(encode(&v).unwrap(), *b);
+            assert_eq!(decode::<Value>(b).unwrap(), v);
+        }
+    }
+
+    #[test]
+    fn octets_len_in() {
+        let v = Value::OctetStr(OctetString::from_static(&[1, 2, 3]));
+        assert!(v.octets_len_in(&[3, 4]));
+        assert!(!v.octets_len_in(&[4]));
+        assert!(!Value::Integer(3).octets_len_in(&[3]));
+    }
+
+    #[test]
+    fn as_mac() {
+        let mac = [0x00, 0x1b, 0x21, 0x3c, 0x4d, 0x5e];
+        let v = Value::OctetStr(OctetString::copy_from_slice(&mac));
+        assert_eq!(v.as_mac(), Some(mac));
+
+        let v = Value::OctetStr(OctetString::copy_from_slice(&mac[..5]));
+        assert_eq!(v.as_mac(), None);
+    }
+
+    #[test]
+    fn as_ipv6() {
+        let addr: Ipv6Addr = "2001:db8::1".parse().unwrap();
+        let v = Value::OctetStr(OctetString::copy_from_slice(&addr.octets()));
+        assert_eq!(v.as_ipv6(), Some(addr));
+
+        let v = Value::OctetStr(OctetString::copy_from_slice(&addr.octets()[..4]));
+        assert_eq!(v.as_ipv6(), None);
+    }
+
+    #[test]
+    fn as_datetime() {
+        // 1992-05-26,13:30:15.0,-4:0
+        let b = [0x07, 0xc8, 5, 26, 13, 30, 15, 0, b'-', 4, 0];
+        let expected = DateAndTime {
+            year: 1992,
+            month: 5,
+            day: 26,
+            hour: 13,
+            minutes: 30,
+            seconds: 15,
+            deci_seconds: 0,
+            utc_offset: Some(('-', 4, 0)),
+        };
+        let v = Value::OctetStr(OctetString::copy_from_slice(&b));
+        assert_eq!(v.as_datetime(), Some(expected));
+
+        let v = Value::OctetStr(OctetString::copy_from_slice(&b[..8]));
+        assert_eq!(
+            v.as_datetime(),
+            Some(DateAndTime {
+                utc_offset: None,
+                ..expected
+            })
+        );
+
+        let v = Value::OctetStr(OctetString::copy_from_slice(&b[..10]));
+        assert_eq!(v.as_datetime(), None);
+
+        let mut bad_direction = b;
+        bad_direction[8] = b'x';
+        let v = Value::OctetStr(OctetString::copy_from_slice(&bad_direction));
+        assert_eq!(v.as_datetime(), None);
+    }
+
+    #[test]
+    fn binding_to_pair() {
+        let vb = VarBinding::new(
+            ObjectIdentifier::new(vec![1, 3, 6, 1, 2, 1, 1, 5, 0]).unwrap(),
+            Value::Integer(7),
+        );
+        assert_eq!(
+            vb.to_pair(),
+            ("1.3.6.1.2.1.1.5.0".to_owned(), Value::Integer(7))
+        );
+
+        let (name, value): (ObjectIdentifier, Value) = vb.into();
+        assert_eq!(name.as_slice(), &[1, 3, 6, 1, 2, 1, 1, 5, 0]);
+        assert_eq!(value, Value::Integer(7));
+    }
 }
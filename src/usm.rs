@@ -0,0 +1,368 @@
+//! The User-based Security Model for SNMPv3 (RFC 3414).
+
+use crate::client::Client;
+use crate::error::SnmpError;
+use crate::transport::Transport;
+use crate::types::{ObjectIdentifier, ObjectIdentifierExt, Value, VarBinding};
+
+use md5::Md5;
+use rasn::types::OctetString;
+use sha1::{Digest, Sha1};
+
+use std::fmt;
+
+// `usmStats` counters an agent returns in a Report PDU when it rejects a
+// message.
+pub const USM_STATS_UNSUPPORTED_SEC_LEVELS: &[u32] = &[1, 3, 6, 1, 6, 3, 15, 1, 1, 1, 0];
+pub const USM_STATS_NOT_IN_TIME_WINDOWS: &[u32] = &[1, 3, 6, 1, 6, 3, 15, 1, 1, 2, 0];
+pub const USM_STATS_UNKNOWN_USER_NAMES: &[u32] = &[1, 3, 6, 1, 6, 3, 15, 1, 1, 3, 0];
+pub const USM_STATS_UNKNOWN_ENGINE_IDS: &[u32] = &[1, 3, 6, 1, 6, 3, 15, 1, 1, 4, 0];
+pub const USM_STATS_WRONG_DIGESTS: &[u32] = &[1, 3, 6, 1, 6, 3, 15, 1, 1, 5, 0];
+pub const USM_STATS_DECRYPTION_ERRORS: &[u32] = &[1, 3, 6, 1, 6, 3, 15, 1, 1, 6, 0];
+
+/// Why an agent rejected an SNMPv3 message, named by the `usmStats` counter
+/// in its Report. All of them point at the client's credentials or security
+/// level rather than at the request.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReportReason {
+    /// `usmStatsUnsupportedSecLevels`: the user may not use the security
+    /// level requested, as with agents only allowing some users
+    /// `noAuthNoPriv`.
+    UnsupportedSecLevel,
+    /// `usmStatsNotInTimeWindows`, still after resynchronizing.
+    NotInTimeWindow,
+    /// `usmStatsUnknownUserNames`.
+    UnknownUserName,
+    /// `usmStatsUnknownEngineIDs`.
+    UnknownEngineId,
+    /// `usmStatsWrongDigests`: usually a wrong authentication password or
+    /// protocol.
+    WrongDigest,
+    /// `usmStatsDecryptionErrors`: usually a wrong privacy password or
+    /// protocol.
+    DecryptionError,
+}
+
+impl ReportReason {
+    /// The reason a Report carrying `oid` gives, or `None` if `oid` is not a
+    /// `usmStats` counter.
+    pub fn from_oid(oid: &ObjectIdentifier) -> Option<ReportReason> {
+        let reason = match oid.as_slice() {
+            USM_STATS_UNSUPPORTED_SEC_LEVELS => ReportReason::UnsupportedSecLevel,
+            USM_STATS_NOT_IN_TIME_WINDOWS => ReportReason::NotInTimeWindow,
+            USM_STATS_UNKNOWN_USER_NAMES => ReportReason::UnknownUserName,
+            USM_STATS_UNKNOWN_ENGINE_IDS => ReportReason::UnknownEngineId,
+            USM_STATS_WRONG_DIGESTS => ReportReason::WrongDigest,
+            USM_STATS_DECRYPTION_ERRORS => ReportReason::DecryptionError,
+            _ => return None,
+        };
+        Some(reason)
+    }
+}
+
+impl fmt::Display for ReportReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            ReportReason::UnsupportedSecLevel => "security level not supported for the user",
+            ReportReason::NotInTimeWindow => "message not in the time window",
+            ReportReason::UnknownUserName => "unknown user name",
+            ReportReason::UnknownEngineId => "unknown engine id",
+            ReportReason::WrongDigest => "wrong authentication digest",
+            ReportReason::DecryptionError => "message could not be decrypted",
+        };
+        f.write_str(reason)
+    }
+}
+
+/// `usmUserAuthKeyChange` and `usmUserPrivKeyChange`, indexed by engine id
+/// and user name.
+const USM_USER_AUTH_KEY_CHANGE: &[u32] = &[1, 3, 6, 1, 6, 3, 15, 1, 2, 2, 1, 6];
+const USM_USER_PRIV_KEY_CHANGE: &[u32] = &[1, 3, 6, 1, 6, 3, 15, 1, 2, 2, 1, 9];
+
+/// Bytes of a localized privacy key, as DES and AES-128 use.
+const PRIV_KEY_LEN: usize = 16;
+
+/// The HMAC hash used to authenticate SNMPv3 messages.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AuthProtocol {
+    Md5,
+    Sha1,
+}
+
+/// Which key of a USM user `Client::change_key` replaces.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AuthOrPriv {
+    Auth,
+    Priv,
+}
+
+/// Bytes of the repeated password hashed by `password_to_key`.
+const PASSWORD_EXPANSION: usize = 1024 * 1024;
+
+fn digest<D: Digest>(parts: &[&[u8]]) -> Vec<u8> {
+    let mut hasher = D::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().to_vec()
+}
+
+fn expand_password<D: Digest>(password: &[u8]) -> Vec<u8> {
+    let mut hasher = D::new();
+    let mut block = [0u8; 64];
+    for offset in (0..PASSWORD_EXPANSION).step_by(block.len()) {
+        for (i, b) in block.iter_mut().enumerate() {
+            *b = password[(offset + i) % password.len()];
+        }
+        hasher.update(block);
+    }
+    hasher.finalize().to_vec()
+}
+
+/// Derives the master key for `password` (RFC 3414 A.2). This is deliberately
+/// slow, so the result is worth caching.
+///
+/// # Panics
+///
+/// Panics if `password` is empty.
+pub fn password_to_key(protocol: AuthProtocol, password: &[u8]) -> Vec<u8> {
+    assert!(!password.is_empty(), "SNMPv3 password must not be empty");
+
+    match protocol {
+        AuthProtocol::Md5 => expand_password::<Md5>(password),
+        AuthProtocol::Sha1 => expand_password::<Sha1>(password),
+    }
+}
+
+/// Localizes a master key from `password_to_key` to the agent identified by
+/// `engine_id` (RFC 3414 2.6).
+pub fn localize_key(protocol: AuthProtocol, key: &[u8], engine_id: &[u8]) -> Vec<u8> {
+    let parts = [key, engine_id, key];
+    match protocol {
+        AuthProtocol::Md5 => digest::<Md5>(&parts),
+        AuthProtocol::Sha1 => digest::<Sha1>(&parts),
+    }
+}
+
+/// The KeyChange value that replaces `old_key` with `new_key` (RFC 3414 5):
+/// `random` followed by `new_key` XORed with digests chained from `old_key`
+/// and `random`.
+///
+/// # Panics
+///
+/// Panics unless the keys and `random` all have the same length.
+pub fn key_change(
+    protocol: AuthProtocol,
+    old_key: &[u8],
+    new_key: &[u8],
+    random: &[u8],
+) -> Vec<u8> {
+    assert!(
+        old_key.len() == new_key.len() && random.len() == new_key.len(),
+        "keys and random value must have the same length"
+    );
+
+    let mut temp = old_key.to_vec();
+    let mut change = random.to_vec();
+    let digest_len = match protocol {
+        AuthProtocol::Md5 => 16,
+        AuthProtocol::Sha1 => 20,
+    };
+    for chunk in new_key.chunks(digest_len) {
+        temp = match protocol {
+            AuthProtocol::Md5 => digest::<Md5>(&[&temp, random]),
+            AuthProtocol::Sha1 => digest::<Sha1>(&[&temp, random]),
+        };
+        change.extend(chunk.iter().zip(&temp).map(|(k, t)| k ^ t));
+    }
+    change
+}
+
+impl<'a, T: Transport> Client<'a, T> {
+    /// Changes the password of this client's security name by SETting
+    /// `usmUserAuthKeyChange` or `usmUserPrivKeyChange` (RFC 3414 5). The keys
+    /// are localized to the agent's engine, which is discovered first if
+    /// needed. Privacy keys are the first 16 bytes of the localized key.
+    ///
+    /// The passwords are hashed with `protocol`, which must be the user's
+    /// authentication protocol, also for its privacy key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either password is empty.
+    pub fn change_key(
+        &mut self,
+        protocol: AuthProtocol,
+        old_password: &[u8],
+        new_password: &[u8],
+        which: AuthOrPriv,
+    ) -> Result<(), SnmpError> {
+        let engine = match self.engine() {
+            Some(engine) => engine.clone(),
+            None => self.discover_engine()?,
+        };
+
+        let localize = |password| {
+            let key = password_to_key(protocol, password);
+            localize_key(protocol, &key, engine.id())
+        };
+        let (mut old_key, mut new_key) = (localize(old_password), localize(new_password));
+        let column = match which {
+            AuthOrPriv::Auth => USM_USER_AUTH_KEY_CHANGE,
+            AuthOrPriv::Priv => {
+                old_key.truncate(PRIV_KEY_LEN);
+                new_key.truncate(PRIV_KEY_LEN);
+                USM_USER_PRIV_KEY_CHANGE
+            }
+        };
+
+        let random: Vec<u8> = (0..new_key.len()).map(|_| rand::random()).collect();
+        let change = key_change(protocol, &old_key, &new_key, &random);
+
+        let mut index = vec![engine.id().len() as u32];
+        index.extend(engine.id().iter().map(|b| u32::from(*b)));
+        let user = self.security_name().as_bytes();
+        index.push(user.len() as u32);
+        index.extend(user.iter().map(|b| u32::from(*b)));
+        let oid = ObjectIdentifier::new(column.to_vec())
+            .unwrap()
+            .child(&index);
+
+        let value = Value::OctetStr(OctetString::from(change));
+        self.set(&[VarBinding::new(oid, value)]).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oid;
+    use crate::testing::*;
+    use crate::types::Version;
+    use crate::v3::EngineInfo;
+
+    use std::sync::mpsc;
+    use std::time::Instant;
+
+    // RFC 3414 A.3
+    const PASSWORD: &[u8] = b"maplesyrup";
+    const ENGINE_ID: &[u8] = &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2];
+
+    #[test]
+    fn md5_key() {
+        let key = password_to_key(AuthProtocol::Md5, PASSWORD);
+        assert_eq!(
+            key,
+            &[
+                0x9f, 0xaf, 0x32, 0x83, 0x88, 0x4e, 0x92, 0x83, 0x4e, 0xbc, 0x98, 0x47, 0xd8, 0xed,
+                0xd9, 0x63
+            ]
+        );
+
+        assert_eq!(
+            localize_key(AuthProtocol::Md5, &key, ENGINE_ID),
+            &[
+                0x52, 0x6f, 0x5e, 0xed, 0x9f, 0xcc, 0xe2, 0x6f, 0x89, 0x64, 0xc2, 0x93, 0x07, 0x87,
+                0xd8, 0x2b
+            ]
+        );
+    }
+
+    #[test]
+    fn sha1_key() {
+        let key = password_to_key(AuthProtocol::Sha1, PASSWORD);
+        assert_eq!(
+            key,
+            &[
+                0x9f, 0xb5, 0xcc, 0x03, 0x81, 0x49, 0x7b, 0x37, 0x93, 0x52, 0x89, 0x39, 0xff, 0x78,
+                0x8d, 0x5d, 0x79, 0x14, 0x52, 0x11
+            ]
+        );
+
+        assert_eq!(
+            localize_key(AuthProtocol::Sha1, &key, ENGINE_ID),
+            &[
+                0x66, 0x95, 0xfe, 0xbc, 0x92, 0x88, 0xe3, 0x62, 0x82, 0x23, 0x5f, 0xc7, 0x15, 0x1f,
+                0x12, 0x84, 0x97, 0xb3, 0x8f, 0x3f
+            ]
+        );
+    }
+
+    // RFC 3414 A.5
+    #[test]
+    fn key_change_values() {
+        let md5 = |password| {
+            localize_key(
+                AuthProtocol::Md5,
+                &password_to_key(AuthProtocol::Md5, password),
+                ENGINE_ID,
+            )
+        };
+        let change = key_change(
+            AuthProtocol::Md5,
+            &md5(PASSWORD),
+            &md5(b"newsyrup"),
+            &[0; 16],
+        );
+        assert_eq!(&change[..16], &[0; 16]);
+        assert_eq!(
+            &change[16..],
+            &[
+                0x88, 0x05, 0x61, 0x51, 0x41, 0x67, 0x6c, 0xc9, 0x19, 0x61, 0x74, 0xe7, 0x42, 0xa3,
+                0x25, 0x51
+            ]
+        );
+
+        let sha1 = |password| {
+            localize_key(
+                AuthProtocol::Sha1,
+                &password_to_key(AuthProtocol::Sha1, password),
+                ENGINE_ID,
+            )
+        };
+        let change = key_change(
+            AuthProtocol::Sha1,
+            &sha1(PASSWORD),
+            &sha1(b"newsyrup"),
+            &[0; 20],
+        );
+        assert_eq!(&change[..20], &[0; 20]);
+        assert_eq!(
+            &change[20..],
+            &[
+                0x9c, 0x10, 0x17, 0xf4, 0xfd, 0x48, 0x3d, 0x2d, 0xe8, 0xd5, 0xfa, 0xdb, 0xf8, 0x43,
+                0x92, 0xcb, 0x06, 0x45, 0x70, 0x51
+            ]
+        );
+    }
+
+    #[test]
+    fn change_priv_key_sets_user_row() {
+        let (tx, rx) = mpsc::channel();
+        let mut socket = agent(move |request| {
+            tx.send(request.data().bindings().to_vec()).unwrap();
+            response(request, request.data().bindings())
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_engine(EngineInfo::new(ENGINE_ID, 1, 0, Instant::now()));
+        c.set_security_name("ab");
+
+        c.change_key(AuthProtocol::Sha1, PASSWORD, b"newsyrup", AuthOrPriv::Priv)
+            .unwrap();
+        let sent = rx.recv().unwrap();
+        let mut column = oid! {1,3,6,1,6,3,15,1,2,2,1,9,12,0,0,0,0,0,0,0,0,0,0,0,2};
+        column.extend_from_slice(&[2, u32::from(b'a'), u32::from(b'b')]);
+        assert_eq!(sent[0].name, column);
+
+        // The agent recovers the new key from the old one and the random half.
+        let change = sent[0].value.as_bytes().unwrap();
+        let (random, delta) = change.split_at(PRIV_KEY_LEN);
+        let key = |password| {
+            let key = password_to_key(AuthProtocol::Sha1, password);
+            localize_key(AuthProtocol::Sha1, &key, ENGINE_ID)
+        };
+        let digest = digest::<Sha1>(&[&key(PASSWORD)[..PRIV_KEY_LEN], random]);
+        let recovered: Vec<_> = delta.iter().zip(&digest).map(|(d, t)| d ^ t).collect();
+        assert_eq!(recovered, &key(b"newsyrup")[..PRIV_KEY_LEN]);
+    }
+}
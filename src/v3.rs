@@ -0,0 +1,649 @@
+//! SNMPv3 message parts (RFC 3412).
+
+use crate::client::Client;
+use crate::codec::{Ber, Codec};
+use crate::error::SnmpError;
+use crate::pdu::{DecodeLimits, Pdu, PduTag};
+use crate::transport::Transport;
+use crate::types::Version;
+use crate::usm::{ReportReason, USM_STATS_NOT_IN_TIME_WINDOWS};
+
+use rasn::de::Error;
+use rasn::types::OctetString;
+use rasn::{enc, AsnType, Decode, Decoder, Encode, Encoder, Tag};
+
+use std::time::Instant;
+
+/// msgFlags bit asking the receiver to answer errors with a Report.
+const FLAG_REPORTABLE: u8 = 0x04;
+
+/// msgSecurityModel of the User-based Security Model.
+const USM_SECURITY_MODEL: i32 = 3;
+
+/// msgMaxSize advertised to agents, an Ethernet frame's worth. The client
+/// receives messages up to `DecodeLimits::max_size` either way.
+const MAX_MESSAGE_SIZE: i32 = 1500;
+
+/// An SNMPv3 message (RFC 3412 6) using the User-based Security Model. The
+/// scoped PDU is carried in plaintext.
+#[derive(Debug)]
+pub struct V3Message {
+    msg_id: i32,
+    max_size: i32,
+    flags: u8,
+    security: SecurityParameters,
+    scoped_pdu: ScopedPdu,
+}
+
+impl V3Message {
+    /// The reportable flag is set for confirmed PDUs (RFC 3412 6.4).
+    pub fn new(msg_id: i32, security: SecurityParameters, scoped_pdu: ScopedPdu) -> V3Message {
+        let flags = match scoped_pdu.data.tag() {
+            PduTag::GetRequest
+            | PduTag::GetNextRequest
+            | PduTag::GetBulkRequest
+            | PduTag::SetRequest
+            | PduTag::InformRequest => FLAG_REPORTABLE,
+            _ => 0,
+        };
+
+        V3Message {
+            msg_id,
+            max_size: MAX_MESSAGE_SIZE,
+            flags,
+            security,
+            scoped_pdu,
+        }
+    }
+
+    pub fn msg_id(&self) -> i32 {
+        self.msg_id
+    }
+
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    /// Overrides the reportable flag `new` derived from the PDU type.
+    pub fn with_reportable(mut self, reportable: bool) -> Self {
+        if reportable {
+            self.flags |= FLAG_REPORTABLE;
+        } else {
+            self.flags &= !FLAG_REPORTABLE;
+        }
+        self
+    }
+
+    pub fn is_reportable(&self) -> bool {
+        self.flags & FLAG_REPORTABLE != 0
+    }
+
+    pub fn security(&self) -> &SecurityParameters {
+        &self.security
+    }
+
+    pub fn scoped_pdu(&self) -> &ScopedPdu {
+        &self.scoped_pdu
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SnmpError> {
+        Ber::encode(self)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<V3Message, SnmpError> {
+        V3Message::from_bytes_with_limits(bytes, DecodeLimits::default())
+    }
+
+    /// Like `Message::from_bytes_with_limits`, for SNMPv3 messages.
+    pub fn from_bytes_with_limits(
+        bytes: &[u8],
+        limits: DecodeLimits,
+    ) -> Result<V3Message, SnmpError> {
+        limits.check_size(bytes)?;
+        limits.decode(bytes)
+    }
+}
+
+impl AsnType for V3Message {
+    const TAG: Tag = Tag::SEQUENCE;
+}
+
+impl Encode for V3Message {
+    fn encode_with_tag<E: Encoder>(&self, encoder: &mut E, tag: Tag) -> Result<(), E::Error> {
+        let security = Ber::encode_raw(&self.security).map_err(<E::Error as enc::Error>::custom)?;
+
+        encoder.encode_sequence(tag, |sequence| {
+            Version::V3.encode(sequence)?;
+            sequence.encode_sequence(Tag::SEQUENCE, |header| {
+                self.msg_id.encode(header)?;
+                self.max_size.encode(header)?;
+                OctetString::copy_from_slice(&[self.flags]).encode(header)?;
+                USM_SECURITY_MODEL.encode(header)?;
+                Ok(())
+            })?;
+            OctetString::from(security).encode(sequence)?;
+            self.scoped_pdu.encode(sequence)?;
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+}
+
+impl Decode for V3Message {
+    fn decode_with_tag<D: Decoder>(decoder: &mut D, tag: Tag) -> Result<Self, D::Error> {
+        let mut seq = decoder.decode_sequence(tag)?;
+        let version = Version::decode(&mut seq)?;
+        if version != Version::V3 {
+            return Err(D::Error::custom(format!(
+                "Expected an SNMPv3 message, found {:?}",
+                version
+            )));
+        }
+
+        let mut header = seq.decode_sequence(Tag::SEQUENCE)?;
+        let msg_id = i32::decode(&mut header)?;
+        let max_size = i32::decode(&mut header)?;
+        let flags = match OctetString::decode(&mut header)?.as_ref() {
+            [flags] => *flags,
+            flags => {
+                return Err(D::Error::custom(format!(
+                    "Expected a single msgFlags octet, found {}",
+                    flags.len()
+                )))
+            }
+        };
+        let security_model = i32::decode(&mut header)?;
+        if security_model != USM_SECURITY_MODEL {
+            return Err(D::Error::custom(format!(
+                "Unsupported security model {}",
+                security_model
+            )));
+        }
+
+        let security = OctetString::decode(&mut seq)?;
+        let security = Ber::decode_raw(&security).map_err(D::Error::custom)?;
+        let scoped_pdu = ScopedPdu::decode(&mut seq)?;
+
+        Ok(V3Message {
+            msg_id,
+            max_size,
+            flags,
+            security,
+            scoped_pdu,
+        })
+    }
+}
+
+/// The USM fields of msgSecurityParameters (RFC 3414 2.4).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SecurityParameters {
+    pub engine_id: Vec<u8>,
+    pub engine_boots: u32,
+    pub engine_time: u32,
+    pub user_name: Vec<u8>,
+    pub auth_params: Vec<u8>,
+    pub priv_params: Vec<u8>,
+}
+
+impl AsnType for SecurityParameters {
+    const TAG: Tag = Tag::SEQUENCE;
+}
+
+impl Encode for SecurityParameters {
+    fn encode_with_tag<E: Encoder>(&self, encoder: &mut E, tag: Tag) -> Result<(), E::Error> {
+        encoder.encode_sequence(tag, |sequence| {
+            OctetString::copy_from_slice(&self.engine_id).encode(sequence)?;
+            self.engine_boots.encode(sequence)?;
+            self.engine_time.encode(sequence)?;
+            OctetString::copy_from_slice(&self.user_name).encode(sequence)?;
+            OctetString::copy_from_slice(&self.auth_params).encode(sequence)?;
+            OctetString::copy_from_slice(&self.priv_params).encode(sequence)?;
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+}
+
+impl Decode for SecurityParameters {
+    fn decode_with_tag<D: Decoder>(decoder: &mut D, tag: Tag) -> Result<Self, D::Error> {
+        let mut seq = decoder.decode_sequence(tag)?;
+
+        Ok(SecurityParameters {
+            engine_id: OctetString::decode(&mut seq)?.to_vec(),
+            engine_boots: u32::decode(&mut seq)?,
+            engine_time: u32::decode(&mut seq)?,
+            user_name: OctetString::decode(&mut seq)?.to_vec(),
+            auth_params: OctetString::decode(&mut seq)?.to_vec(),
+            priv_params: OctetString::decode(&mut seq)?.to_vec(),
+        })
+    }
+}
+
+/// An agent's authoritative engine as last reported by the agent, with the
+/// local time of that report so its clock can be followed (RFC 3414 2.3).
+#[derive(Clone, Debug, PartialEq)]
+pub struct EngineInfo {
+    id: Vec<u8>,
+    boots: u32,
+    time: u32,
+    synced_at: Instant,
+}
+
+impl EngineInfo {
+    pub fn new(id: &[u8], boots: u32, time: u32, synced_at: Instant) -> EngineInfo {
+        EngineInfo {
+            id: id.to_vec(),
+            boots,
+            time,
+            synced_at,
+        }
+    }
+
+    fn from_parameters(security: &SecurityParameters, synced_at: Instant) -> EngineInfo {
+        EngineInfo::new(
+            &security.engine_id,
+            security.engine_boots,
+            security.engine_time,
+            synced_at,
+        )
+    }
+
+    pub fn id(&self) -> &[u8] {
+        &self.id
+    }
+
+    pub fn boots(&self) -> u32 {
+        self.boots
+    }
+
+    /// The engineTime the agent reported when last synchronized.
+    pub fn synced_time(&self) -> u32 {
+        self.time
+    }
+
+    /// When the engine was last synchronized, by the client's clock.
+    pub fn synced_at(&self) -> Instant {
+        self.synced_at
+    }
+
+    /// The agent's engineTime at `now`, advanced by the time elapsed locally
+    /// since it was reported.
+    pub fn time_at(&self, now: Instant) -> u32 {
+        let elapsed = now.saturating_duration_since(self.synced_at).as_secs();
+        self.time
+            .saturating_add(elapsed.min(u64::from(u32::MAX)) as u32)
+    }
+}
+
+/// A PDU with the context it is addressed to.
+#[derive(Debug)]
+pub struct ScopedPdu {
+    context_engine_id: OctetString,
+    context_name: OctetString,
+    data: Pdu,
+}
+
+impl ScopedPdu {
+    pub fn new(context_engine_id: &[u8], context_name: &str, data: Pdu) -> ScopedPdu {
+        ScopedPdu {
+            context_engine_id: OctetString::copy_from_slice(context_engine_id),
+            context_name: OctetString::copy_from_slice(context_name.as_bytes()),
+            data,
+        }
+    }
+
+    pub fn context_engine_id(&self) -> &[u8] {
+        &self.context_engine_id
+    }
+
+    pub fn context_name(&self) -> &[u8] {
+        &self.context_name
+    }
+
+    pub fn data(&self) -> &Pdu {
+        &self.data
+    }
+}
+
+impl AsnType for ScopedPdu {
+    const TAG: Tag = Tag::SEQUENCE;
+}
+
+impl Encode for ScopedPdu {
+    fn encode_with_tag<E: Encoder>(&self, encoder: &mut E, tag: Tag) -> Result<(), E::Error> {
+        encoder.encode_sequence(tag, |sequence| {
+            self.context_engine_id.encode(sequence)?;
+            self.context_name.encode(sequence)?;
+            self.data.encode(sequence)?;
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+}
+
+impl Decode for ScopedPdu {
+    fn decode_with_tag<D: Decoder>(decoder: &mut D, tag: Tag) -> Result<Self, D::Error> {
+        let mut seq = decoder.decode_sequence(tag)?;
+        let context_engine_id = OctetString::decode(&mut seq)?;
+        let context_name = OctetString::decode(&mut seq)?;
+        let data = Pdu::decode(&mut seq)?;
+
+        Ok(ScopedPdu {
+            context_engine_id,
+            context_name,
+            data,
+        })
+    }
+}
+
+impl<'a, T: Transport> Client<'a, T> {
+    /// Learns the agent's engine id, engineBoots and engineTime from the
+    /// Report it answers an unauthenticated probe with (RFC 3414 4).
+    pub fn discover_engine(&mut self) -> Result<EngineInfo, SnmpError> {
+        let pdu = Pdu::new(PduTag::GetRequest, self.increment_request());
+        let msg = V3Message::new(
+            self.increment_request(),
+            SecurityParameters::default(),
+            ScopedPdu::new(&[], "", pdu),
+        );
+
+        let response = self.exchange_v3_message(&msg)?;
+        if response.scoped_pdu.data.tag() != PduTag::Report {
+            return Err(SnmpError::Decode(format!(
+                "Expected a Report for engine discovery, found {:?}",
+                response.scoped_pdu.data.tag()
+            )));
+        }
+
+        let engine = EngineInfo::from_parameters(&response.security, self.now());
+        self.set_engine(engine.clone());
+        Ok(engine)
+    }
+
+    /// Sends `pdu` in an SNMPv3 message, discovering the engine first if
+    /// needed, and returns the response.
+    ///
+    /// Once the local estimate of the agent's engineTime drifts out of the
+    /// 150 second time window the agent answers `usmStatsNotInTimeWindows`.
+    /// The engine is then resynchronized from that Report and the request
+    /// retried once. Any other `usmStats` Report fails with
+    /// `SnmpError::V3Auth`, and the rest with `SnmpError::Report`.
+    pub(crate) fn exchange_v3(&mut self, pdu: Pdu) -> Result<V3Message, SnmpError> {
+        let mut engine = match self.engine() {
+            Some(engine) => engine.clone(),
+            None => self.discover_engine()?,
+        };
+
+        let mut resynced = false;
+        loop {
+            let security = SecurityParameters {
+                engine_id: engine.id().to_vec(),
+                engine_boots: engine.boots(),
+                engine_time: engine.time_at(self.now()),
+                user_name: self.security_name().as_bytes().to_vec(),
+                ..SecurityParameters::default()
+            };
+            let scoped_pdu = self.scoped_pdu(pdu.clone(), engine.id());
+            let msg = V3Message::new(self.increment_request(), security, scoped_pdu);
+
+            let response = self.exchange_v3_message(&msg)?;
+            match response.scoped_pdu.data.report_stat() {
+                None => return Ok(response),
+                Some((oid, _)) if oid.as_slice() == USM_STATS_NOT_IN_TIME_WINDOWS && !resynced => {
+                    engine = EngineInfo::from_parameters(&response.security, self.now());
+                    self.set_engine(engine.clone());
+                    resynced = true;
+                }
+                Some((oid, _)) => match ReportReason::from_oid(&oid) {
+                    Some(reason) => return Err(SnmpError::V3Auth(reason)),
+                    None => return Err(SnmpError::Report(oid)),
+                },
+            }
+        }
+    }
+
+    fn exchange_v3_message(&mut self, msg: &V3Message) -> Result<V3Message, SnmpError> {
+        let msg_id = msg.msg_id;
+        let limits = self.decode_limits();
+        self.exchange_bytes(&msg.to_bytes()?, |buf| {
+            let response = V3Message::from_bytes_with_limits(buf, limits)?;
+            Ok(Some(response).filter(|response| response.msg_id == msg_id))
+        })
+    }
+
+    /// Wraps `pdu` in this client's context. Without an explicit context
+    /// engine id, the agent's `authoritative_engine_id` is used.
+    pub fn scoped_pdu(&self, pdu: Pdu, authoritative_engine_id: &[u8]) -> ScopedPdu {
+        let (context_engine_id, context_name) = self.context();
+        let context_engine_id = context_engine_id.unwrap_or(authoritative_engine_id);
+
+        ScopedPdu::new(context_engine_id, context_name, pdu)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oid;
+    use crate::pdu::PduTag;
+    use crate::testing::*;
+    use crate::types::{ObjectIdentifier, Value, VarBinding, Version};
+    use crate::usm::{USM_STATS_UNKNOWN_ENGINE_IDS, USM_STATS_WRONG_DIGESTS};
+
+    use rasn::ber::{decode, encode};
+
+    use std::sync::mpsc;
+
+    #[test]
+    fn scoped_pdu_carries_context() {
+        let mut socket = local_socket();
+        let mut c = Client::new(Version::V3, &mut socket);
+        let engine_id: &[u8] = &[0x80, 0, 0x1f, 0x88, 4];
+
+        let pdu = Pdu::new(PduTag::GetRequest, 1).with_null_bindings(&[oid! {1,3,6,1}]);
+        let scoped = c.scoped_pdu(pdu, engine_id);
+        assert_eq!(scoped.context_engine_id(), engine_id);
+        assert_eq!(scoped.context_name(), b"");
+
+        c.set_context(Some(vec![1, 2, 3]), "vrf-blue");
+        let pdu = Pdu::new(PduTag::GetRequest, 2).with_null_bindings(&[oid! {1,3,6,1}]);
+        let b = encode(&c.scoped_pdu(pdu, engine_id)).unwrap();
+
+        let name: &[u8] = &[4, 8, b'v', b'r', b'f', b'-', b'b', b'l', b'u', b'e'];
+        assert_eq!(&b[2..7], &[4, 3, 1, 2, 3]);
+        assert_eq!(&b[7..17], name);
+
+        let scoped = decode::<ScopedPdu>(&b).unwrap();
+        assert_eq!(scoped.context_engine_id(), &[1, 2, 3]);
+        assert_eq!(scoped.context_name(), b"vrf-blue");
+        assert_eq!(scoped.data().request_id(), 2);
+    }
+
+    #[test]
+    fn message_round_trip() {
+        let security = SecurityParameters {
+            engine_id: vec![0x80, 0, 0x1f, 0x88, 4],
+            engine_boots: 3,
+            engine_time: 1_000_000,
+            user_name: b"admin".to_vec(),
+            ..SecurityParameters::default()
+        };
+        let pdu = Pdu::new(PduTag::GetRequest, 7).with_null_bindings(&[oid! {1,3,6,1}]);
+        let msg = V3Message::new(42, security.clone(), ScopedPdu::new(&[1], "", pdu));
+
+        let b = msg.to_bytes().unwrap();
+        assert_eq!(&b[2..5], &[2, 1, 3]);
+
+        let msg = V3Message::from_bytes(&b).unwrap();
+        assert_eq!(msg.msg_id(), 42);
+        assert_eq!(msg.flags(), FLAG_REPORTABLE);
+        assert_eq!(msg.security(), &security);
+        assert_eq!(msg.scoped_pdu().data().request_id(), 7);
+    }
+
+    #[test]
+    fn authenticated_message_encoding_is_deterministic() {
+        // Both ends compute the digest over the message with zeroed
+        // msgAuthenticationParameters and then write it in their place
+        // (RFC 3414 6.3.1), so the bytes may depend on nothing but the
+        // contents.
+        let message = |auth_params: Vec<u8>| {
+            let security = SecurityParameters {
+                engine_id: vec![0x80, 0, 0x1f, 0x88, 4],
+                engine_boots: 3,
+                engine_time: 1_000_000,
+                user_name: b"admin".to_vec(),
+                auth_params,
+                ..SecurityParameters::default()
+            };
+            let pdu = Pdu::new(PduTag::GetRequest, 7).with_null_bindings(&[oid! {1,3,6,1}]);
+            V3Message::new(42, security, ScopedPdu::new(&[1], "", pdu))
+        };
+
+        let zeroed = message(vec![0; 12]).to_bytes().unwrap();
+        assert_eq!(message(vec![0; 12]).to_bytes().unwrap(), zeroed);
+
+        let signed = message(vec![0xa5; 12]).to_bytes().unwrap();
+        assert_eq!(signed.len(), zeroed.len());
+        let differing: Vec<_> = (0..signed.len())
+            .filter(|&i| signed[i] != zeroed[i])
+            .collect();
+        let at = signed.windows(14).position(|w| w[..2] == [4, 12]).unwrap() + 2;
+        assert_eq!(differing, (at..at + 12).collect::<Vec<_>>());
+
+        let received = V3Message::from_bytes(&signed).unwrap();
+        assert_eq!(received.to_bytes().unwrap(), signed);
+    }
+
+    #[test]
+    fn reportable_flag_follows_pdu_type() {
+        let flags_on_wire = |msg: &V3Message| {
+            // msgMaxSize 1500, then the one-byte msgFlags OCTET STRING.
+            let b = msg.to_bytes().unwrap();
+            let i = b.windows(6).position(|w| w == [2, 2, 5, 0xdc, 4, 1]);
+            b[i.unwrap() + 6]
+        };
+        let message = |tag| {
+            let pdu = Pdu::new(tag, 1).with_null_bindings(&[oid! {1,3,6,1}]);
+            V3Message::new(
+                1,
+                SecurityParameters::default(),
+                ScopedPdu::new(&[1], "", pdu),
+            )
+        };
+
+        let get = message(PduTag::GetRequest);
+        assert!(get.is_reportable());
+        assert_eq!(flags_on_wire(&get), 0x04);
+
+        let trap = message(PduTag::TrapV2);
+        assert!(!trap.is_reportable());
+        assert_eq!(flags_on_wire(&trap), 0x00);
+
+        assert_eq!(flags_on_wire(&trap.with_reportable(true)), 0x04);
+        assert_eq!(flags_on_wire(&get.with_reportable(false)), 0x00);
+    }
+
+    /// Answers a request with a Report carrying `stat`, as the engine
+    /// `security` describes.
+    fn report(request: &V3Message, security: SecurityParameters, stat: &[u32]) -> Vec<u8> {
+        let binding = VarBinding::new(
+            ObjectIdentifier::new(stat.to_vec()).unwrap(),
+            Value::Counter32(1),
+        );
+        let pdu = Pdu::new(PduTag::Report, request.scoped_pdu().data().request_id())
+            .with_bindings(&[binding]);
+        let scoped_pdu = ScopedPdu::new(&security.engine_id, "", pdu);
+
+        V3Message::new(request.msg_id(), security, scoped_pdu)
+            .to_bytes()
+            .unwrap()
+    }
+
+    #[test]
+    fn not_in_time_window_resynchronizes() {
+        let engine_id = vec![0x80, 0, 0x1f, 0x88, 4];
+        let engine = move |boots, time| SecurityParameters {
+            engine_id: engine_id.clone(),
+            engine_boots: boots,
+            engine_time: time,
+            ..SecurityParameters::default()
+        };
+
+        let (tx, rx) = mpsc::channel();
+        let mut requests = 0;
+        let mut socket = raw_agent(move |buf| {
+            let request = V3Message::from_bytes(buf).unwrap();
+            tx.send(request.security().clone()).unwrap();
+            requests += 1;
+
+            let response = match requests {
+                1 => report(&request, engine(1, 100), USM_STATS_UNKNOWN_ENGINE_IDS),
+                // The agent rebooted since the discovery.
+                2 => report(&request, engine(2, 5), USM_STATS_NOT_IN_TIME_WINDOWS),
+                _ => {
+                    let binding = VarBinding::new(oid! {1,3,6,1,2,1,1,5,0}, Value::Integer(1));
+                    let pdu = Pdu::new(
+                        PduTag::GetResponse,
+                        request.scoped_pdu().data().request_id(),
+                    )
+                    .with_bindings(&[binding]);
+                    let scoped_pdu =
+                        ScopedPdu::new(request.scoped_pdu().context_engine_id(), "", pdu);
+                    V3Message::new(request.msg_id(), engine(2, 5), scoped_pdu)
+                        .to_bytes()
+                        .unwrap()
+                }
+            };
+            vec![response]
+        });
+
+        let mut c = Client::new(Version::V3, &mut socket);
+        c.set_security_name("admin");
+        let r = c.get(&[oid! {1,3,6,1,2,1,1,5,0}]).unwrap();
+        assert_eq!(r[0].value, Value::Integer(1));
+
+        assert_eq!(rx.recv().unwrap(), SecurityParameters::default());
+        let stale = rx.recv().unwrap();
+        assert_eq!(
+            (stale.engine_boots, stale.user_name.as_slice()),
+            (1, &b"admin"[..])
+        );
+        let retried = rx.recv().unwrap();
+        assert_eq!(retried.engine_boots, 2);
+        assert!(retried.engine_time >= 5);
+
+        let engine = c.engine().unwrap();
+        assert_eq!((engine.boots(), engine.synced_time()), (2, 5));
+    }
+
+    #[test]
+    fn wrong_digest_report_is_an_auth_error() {
+        let engine = SecurityParameters {
+            engine_id: vec![0x80, 0, 0x1f, 0x88, 4],
+            engine_boots: 1,
+            engine_time: 100,
+            ..SecurityParameters::default()
+        };
+        let mut socket = raw_agent(move |buf| {
+            let request = V3Message::from_bytes(buf).unwrap();
+            let stat = if request.security().engine_id.is_empty() {
+                USM_STATS_UNKNOWN_ENGINE_IDS
+            } else {
+                USM_STATS_WRONG_DIGESTS
+            };
+            vec![report(&request, engine.clone(), stat)]
+        });
+
+        let mut c = Client::new(Version::V3, &mut socket);
+        c.set_security_name("admin");
+        let r = c.get(&[oid! {1,3,6,1,2,1,1,5,0}]);
+        assert!(matches!(
+            r,
+            Err(SnmpError::V3Auth(ReportReason::WrongDigest))
+        ));
+    }
+}
@@ -0,0 +1,917 @@
+//! Subtree walks built on GetNext and GetBulk.
+
+use crate::client::Client;
+use crate::error::{ErrorStatus, SnmpError};
+use crate::transport::Transport;
+use crate::types::{ObjectIdentifier, Value, VarBinding, Version};
+
+use std::time::Instant;
+
+/// Why a walk stopped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StopReason {
+    /// The agent returned an OID outside the walked subtree.
+    EndOfSubtree,
+    /// The agent has nothing past the last OID: an `EndOfMIBView` value, or a
+    /// `noSuchName` error-status from an SNMPv1 agent.
+    EndOfMibView,
+    /// The walk collected its maximum number of bindings. The subtree may
+    /// continue past the last one.
+    LimitReached,
+    /// The agent returned an OID the walk had already collected, so it would
+    /// otherwise loop forever.
+    DuplicateOid,
+    /// A lenient walk received an OID that does not follow the last one; see
+    /// `WalkStrictness::Lenient`.
+    NonIncreasingOid,
+    /// The walk ran out of the time `Client::set_walk_budget` allows it. The
+    /// subtree may continue past the last binding.
+    BudgetExhausted,
+}
+
+/// How a walk treats an agent returning an OID that does not follow the last
+/// one collected. OIDs the walk already collected always end it with
+/// `StopReason::DuplicateOid`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WalkStrictness {
+    /// Fails with `SnmpError::NonIncreasingOid`. The default.
+    Strict,
+    /// Ends the walk with `StopReason::NonIncreasingOid` when the OID is the
+    /// last of the subtree, as buggy agents send at the end of a table. In a
+    /// GetBulk response the bindings after it must leave the subtree; a
+    /// GetNext walk asks once for the OID after it, which must leave the
+    /// subtree too. Otherwise the agent is looping and the walk still fails.
+    Lenient,
+}
+
+/// The bindings collected by a limited walk and why it stopped.
+#[derive(Clone, Debug)]
+pub struct WalkOutcome {
+    /// The root of the subtree walked, which every binding is under.
+    pub root: ObjectIdentifier,
+    pub bindings: Vec<VarBinding>,
+    pub reason: StopReason,
+    /// When the walk ended on `EndOfSubtree`, the OID past the subtree the
+    /// agent returned. Everything the agent sent after it in the same
+    /// response is dropped.
+    pub boundary: Option<ObjectIdentifier>,
+}
+
+impl WalkOutcome {
+    fn new(root: &ObjectIdentifier) -> WalkOutcome {
+        WalkOutcome {
+            root: root.clone(),
+            bindings: vec![],
+            reason: StopReason::LimitReached,
+            boundary: None,
+        }
+    }
+
+    /// Ends the walk on `var` for `reason`.
+    fn stop(&mut self, reason: StopReason, var: VarBinding) {
+        if reason == StopReason::EndOfSubtree {
+            self.boundary = Some(var.name);
+        }
+        self.reason = reason;
+    }
+
+    /// The OID to continue the walk from.
+    fn last(&self) -> &ObjectIdentifier {
+        self.bindings.last().map_or(&self.root, |vb| &vb.name)
+    }
+
+    /// Whether `name` was collected already. Walks only collect increasing
+    /// OIDs, so the bindings are sorted.
+    fn contains(&self, name: &ObjectIdentifier) -> bool {
+        self.bindings
+            .binary_search_by(|vb| vb.name.as_slice().cmp(name.as_slice()))
+            .is_ok()
+    }
+}
+
+impl<'a, T: Transport> Client<'a, T> {
+    /// Walks the subtree under `root` with GetNext requests.
+    ///
+    /// The walk ends when the agent leaves the subtree or signals the end of
+    /// its MIB view. SNMPv2c agents do this with an `EndOfMIBView` value, SNMPv1
+    /// agents answer with a `noSuchName` error-status instead.
+    ///
+    /// Only the bindings collected so far are returned if the walk runs out
+    /// of its `set_walk_budget`.
+    pub fn walk(&mut self, root: &ObjectIdentifier) -> Result<Vec<VarBinding>, SnmpError> {
+        self.walk_limited(root, usize::MAX)
+            .map(|outcome| outcome.bindings)
+    }
+
+    /// Like `walk`, but stops once `max_bindings` bindings have been collected.
+    pub fn walk_limited(
+        &mut self,
+        root: &ObjectIdentifier,
+        max_bindings: usize,
+    ) -> Result<WalkOutcome, SnmpError> {
+        let mut outcomes = self.walk_many_limited(std::slice::from_ref(root), max_bindings)?;
+        Ok(outcomes.pop().unwrap_or_else(|| WalkOutcome::new(root)))
+    }
+
+    /// Walks several subtrees at once, asking for the next OID of every
+    /// unfinished subtree in the same GetNext request. The results are grouped
+    /// in the same order as `roots`.
+    pub fn walk_many(
+        &mut self,
+        roots: &[ObjectIdentifier],
+    ) -> Result<Vec<Vec<VarBinding>>, SnmpError> {
+        let outcomes = self.walk_many_limited(roots, usize::MAX)?;
+        Ok(outcomes
+            .into_iter()
+            .map(|outcome| outcome.bindings)
+            .collect())
+    }
+
+    /// Walks with GetNext from `start` up to, but not including,
+    /// `end_exclusive`, regardless of subtree boundaries. `start` itself is
+    /// not returned.
+    pub fn walk_range(
+        &mut self,
+        start: &ObjectIdentifier,
+        end_exclusive: &ObjectIdentifier,
+    ) -> Result<Vec<VarBinding>, SnmpError> {
+        let deadline = self.walk_deadline();
+        let mut vars = vec![];
+        let mut last = start.clone();
+        while !self.past(deadline) {
+            let var = match self.get_next(std::slice::from_ref(&last)) {
+                Ok(mut next) => match next.pop() {
+                    Some(var) => var,
+                    None => break,
+                },
+                Err(SnmpError::Agent {
+                    status: ErrorStatus::NoSuchName,
+                    ..
+                }) if self.version() == Version::V1 => break,
+                Err(e) => return Err(e),
+            };
+
+            if var.value == Value::EndOfMIBView || var.name.as_slice() >= end_exclusive.as_slice() {
+                break;
+            }
+            if var.name.as_slice() <= last.as_slice() {
+                if self.walk_strictness() == WalkStrictness::Lenient
+                    && self.leaves_after(&var, |next| {
+                        next.name.as_slice() >= end_exclusive.as_slice()
+                    })?
+                {
+                    break;
+                }
+                return Err(SnmpError::NonIncreasingOid(var.name));
+            }
+
+            last = var.name.clone();
+            vars.push(var);
+        }
+
+        Ok(vars)
+    }
+
+    /// Walks the subtree under `root` with GetBulk requests, asking for up to
+    /// `max_repetitions` bindings at a time. Not available in SNMPv1.
+    pub fn bulk_walk(
+        &mut self,
+        root: &ObjectIdentifier,
+        max_repetitions: i32,
+    ) -> Result<Vec<VarBinding>, SnmpError> {
+        self.bulk_walk_limited(root, max_repetitions, usize::MAX)
+            .map(|outcome| outcome.bindings)
+    }
+
+    /// Like `bulk_walk`, but stops once `max_bindings` bindings have been
+    /// collected. Bindings past the limit in the last response are dropped.
+    pub fn bulk_walk_limited(
+        &mut self,
+        root: &ObjectIdentifier,
+        max_repetitions: i32,
+        max_bindings: usize,
+    ) -> Result<WalkOutcome, SnmpError> {
+        if self.version() == Version::V1 {
+            return Err(SnmpError::Unsupported("GetBulk in SNMPv1"));
+        }
+
+        let deadline = self.walk_deadline();
+        let mut outcome = WalkOutcome::new(root);
+        while outcome.bindings.len() < max_bindings {
+            if self.past(deadline) {
+                outcome.reason = StopReason::BudgetExhausted;
+                break;
+            }
+            let last = outcome.last().clone();
+            let vars = self.get_bulk(&[], max_repetitions, std::slice::from_ref(&last))?;
+            if vars.is_empty() {
+                outcome.reason = StopReason::EndOfMibView;
+                return Ok(outcome);
+            }
+
+            let mut vars = vars.into_iter().peekable();
+            while let Some(var) = vars.next() {
+                if let Some(reason) = end_of_walk(&var, &outcome) {
+                    outcome.stop(reason, var);
+                    return Ok(outcome);
+                }
+                if var.name.as_slice() <= outcome.last().as_slice() {
+                    let last_of_subtree = vars
+                        .peek()
+                        .is_none_or(|next| end_of_walk(next, &outcome).is_some());
+                    if self.walk_strictness() == WalkStrictness::Lenient && last_of_subtree {
+                        outcome.reason = StopReason::NonIncreasingOid;
+                        return Ok(outcome);
+                    }
+                    return Err(SnmpError::NonIncreasingOid(var.name));
+                }
+                if outcome.bindings.len() == max_bindings {
+                    break;
+                }
+
+                outcome.bindings.push(var);
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    /// Walks several subtrees at once with GetBulk, such as the columns of a
+    /// table, with one repeater for every unfinished subtree. Subtrees end at
+    /// different points; once one does it is left out of later requests while
+    /// the others continue. The results are grouped in the same order as
+    /// `roots`. Not available in SNMPv1.
+    pub fn bulk_walk_many(
+        &mut self,
+        roots: &[ObjectIdentifier],
+        max_repetitions: i32,
+    ) -> Result<Vec<Vec<VarBinding>>, SnmpError> {
+        if self.version() == Version::V1 {
+            return Err(SnmpError::Unsupported("GetBulk in SNMPv1"));
+        }
+
+        let mut outcomes: Vec<_> = roots.iter().map(WalkOutcome::new).collect();
+        // Indexes into roots of the unfinished subtrees, in the order they
+        // appear in the request.
+        let mut active: Vec<usize> = (0..roots.len()).collect();
+        let deadline = self.walk_deadline();
+        let mut oids = Vec::with_capacity(roots.len());
+        while !active.is_empty() && !self.past(deadline) {
+            oids.clear();
+            oids.extend(active.iter().map(|&i| outcomes[i].last().clone()));
+            let vars = self.get_bulk(&[], max_repetitions, &oids)?;
+            if vars.is_empty() {
+                break;
+            }
+
+            // The response repeats a row of one binding per repeater, so a
+            // subtree keeps its column even after it ends. Its bindings past
+            // that, usually more `EndOfMIBView`s, are skipped.
+            let mut ended = vec![false; active.len()];
+            // The OID a lenient walk stepped back to in each column, until
+            // the column's next binding shows whether it left the subtree.
+            let mut stepped_back = vec![None; active.len()];
+            for (column, var) in (0..active.len()).cycle().zip(vars) {
+                let i = active[column];
+                if let Some(name) = stepped_back[column].take() {
+                    if end_of_walk(&var, &outcomes[i]).is_none() {
+                        return Err(SnmpError::NonIncreasingOid(name));
+                    }
+                    continue;
+                }
+                if ended[column] {
+                    continue;
+                }
+                if let Some(reason) = end_of_walk(&var, &outcomes[i]) {
+                    outcomes[i].stop(reason, var);
+                    ended[column] = true;
+                    continue;
+                }
+                if var.name.as_slice() <= outcomes[i].last().as_slice() {
+                    if self.walk_strictness() == WalkStrictness::Lenient {
+                        ended[column] = true;
+                        stepped_back[column] = Some(var.name);
+                        continue;
+                    }
+                    return Err(SnmpError::NonIncreasingOid(var.name));
+                }
+
+                outcomes[i].bindings.push(var);
+            }
+            active = active
+                .into_iter()
+                .zip(ended)
+                .filter(|(_, ended)| !ended)
+                .map(|(i, _)| i)
+                .collect();
+        }
+
+        Ok(outcomes
+            .into_iter()
+            .map(|outcome| outcome.bindings)
+            .collect())
+    }
+
+    /// Like `walk_many`, but stops every subtree once `max_bindings` bindings
+    /// have been collected between them. Each outcome names the root it
+    /// walked, so results stay attributed when roots overlap or outcomes are
+    /// merged.
+    pub fn walk_many_limited(
+        &mut self,
+        roots: &[ObjectIdentifier],
+        max_bindings: usize,
+    ) -> Result<Vec<WalkOutcome>, SnmpError> {
+        let mut outcomes: Vec<_> = roots.iter().map(WalkOutcome::new).collect();
+        let mut collected = 0;
+        // Indexes into roots of the unfinished subtrees, in the order they
+        // appear in the request.
+        let mut active: Vec<usize> = (0..roots.len()).collect();
+        let deadline = self.walk_deadline();
+        // The OIDs requested and the subtrees continuing, reused every round.
+        let mut oids = Vec::with_capacity(roots.len());
+        let mut next = Vec::with_capacity(roots.len());
+
+        while !active.is_empty() && collected < max_bindings {
+            if self.past(deadline) {
+                for &i in &active {
+                    outcomes[i].reason = StopReason::BudgetExhausted;
+                }
+                break;
+            }
+            oids.clear();
+            oids.extend(active.iter().map(|&i| outcomes[i].last().clone()));
+            // Subtrees may have reached the same OID, so the request must not
+            // collapse repeats.
+            let vars = match self.get_next_each(&oids) {
+                Ok(vars) => vars,
+                Err(SnmpError::Agent {
+                    status: ErrorStatus::NoSuchName,
+                    index,
+                    ..
+                }) if self.version() == Version::V1
+                    && index >= 1
+                    && index as usize <= active.len() =>
+                {
+                    let i = active.remove(index as usize - 1);
+                    outcomes[i].reason = StopReason::EndOfMibView;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            let mut vars = vars.into_iter();
+            next.clear();
+            for (&i, last) in active.iter().zip(&oids) {
+                let var = match vars.next() {
+                    Some(var) => var,
+                    None => {
+                        outcomes[i].reason = StopReason::EndOfMibView;
+                        continue;
+                    }
+                };
+                if let Some(reason) = end_of_walk(&var, &outcomes[i]) {
+                    outcomes[i].stop(reason, var);
+                    continue;
+                }
+                if var.name.as_slice() <= last.as_slice() {
+                    let root = &outcomes[i].root;
+                    if self.walk_strictness() == WalkStrictness::Lenient
+                        && self.leaves_after(&var, |next| {
+                            !next.name.as_slice().starts_with(root.as_slice())
+                        })?
+                    {
+                        outcomes[i].reason = StopReason::NonIncreasingOid;
+                        continue;
+                    }
+                    return Err(SnmpError::NonIncreasingOid(var.name));
+                }
+                if collected == max_bindings {
+                    continue;
+                }
+
+                collected += 1;
+                next.push(i);
+                outcomes[i].bindings.push(var);
+            }
+            std::mem::swap(&mut active, &mut next);
+        }
+
+        Ok(outcomes)
+    }
+
+    /// Whether a lenient GetNext walk may end on `var`, an OID that does not
+    /// follow the last one: only if the OID the agent has after it is past
+    /// the walk, as `past_walk` decides, or the agent has nothing after it.
+    fn leaves_after(
+        &mut self,
+        var: &VarBinding,
+        past_walk: impl Fn(&VarBinding) -> bool,
+    ) -> Result<bool, SnmpError> {
+        match self.get_next(std::slice::from_ref(&var.name)) {
+            Ok(next) => Ok(next
+                .first()
+                .is_none_or(|next| next.value == Value::EndOfMIBView || past_walk(next))),
+            Err(SnmpError::Agent {
+                status: ErrorStatus::NoSuchName,
+                ..
+            }) if self.version() == Version::V1 => Ok(true),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// When a walk starting now runs out of its budget.
+    fn walk_deadline(&self) -> Option<Instant> {
+        self.walk_budget().map(|budget| self.now() + budget)
+    }
+
+    fn past(&self, deadline: Option<Instant>) -> bool {
+        deadline.is_some_and(|deadline| self.now() >= deadline)
+    }
+}
+
+/// Why `var` ends the walk `outcome` has collected so far, if it does.
+fn end_of_walk(var: &VarBinding, outcome: &WalkOutcome) -> Option<StopReason> {
+    if var.value == Value::EndOfMIBView {
+        Some(StopReason::EndOfMibView)
+    } else if !var.name.as_slice().starts_with(outcome.root.as_slice()) {
+        Some(StopReason::EndOfSubtree)
+    } else if outcome.contains(&var.name) {
+        Some(StopReason::DuplicateOid)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oid;
+    use crate::pdu::{Message, Pdu, PduTag};
+    use crate::testing::*;
+    use crate::types::ObjectIdentifierExt;
+
+    use crate::clock::MockClock;
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    fn system_mib() -> Vec<VarBinding> {
+        vec![
+            VarBinding::new(oid! {1,3,6,1,2,1,1,1,0}, Value::Integer(1)),
+            VarBinding::new(oid! {1,3,6,1,2,1,1,5,0}, Value::Integer(5)),
+        ]
+    }
+
+    #[test]
+    fn walk_v2c_ends_on_end_of_mib_view() {
+        let mut socket = agent(get_next_agent(system_mib()));
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        let vars = c.walk(&oid! {1,3,6}).unwrap();
+        assert_eq!(vars.len(), 2);
+        assert_eq!(vars[1].name, oid! {1,3,6,1,2,1,1,5,0});
+    }
+
+    #[test]
+    fn walk_v1_ends_on_no_such_name() {
+        let mut socket = agent(get_next_agent(system_mib()));
+        let mut c = Client::new(Version::V1, &mut socket);
+
+        let vars = c.walk(&oid! {1,3,6}).unwrap();
+        assert_eq!(vars.len(), 2);
+        assert_eq!(vars[0].name, oid! {1,3,6,1,2,1,1,1,0});
+    }
+
+    #[test]
+    fn walk_v2c_propagates_no_such_name() {
+        let mut socket = agent(|request| {
+            let pdu = Pdu::new(PduTag::GetResponse, request.data().request_id()).with_error(2, 1);
+            Message::with_raw_community(request.version(), request.community_bytes(), pdu)
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        let r = c.walk(&oid! {1,3,6});
+        assert!(matches!(
+            r,
+            Err(SnmpError::Agent {
+                status: ErrorStatus::NoSuchName,
+                index: 1,
+                ..
+            })
+        ));
+    }
+
+    fn two_subtree_mib() -> Vec<VarBinding> {
+        vec![
+            VarBinding::new(oid! {1,3,6,1,2,1,1,1,0}, Value::Integer(1)),
+            VarBinding::new(oid! {1,3,6,1,2,1,1,5,0}, Value::Integer(5)),
+            VarBinding::new(oid! {1,3,6,1,2,1,2,1,0}, Value::Integer(2)),
+            VarBinding::new(oid! {1,3,6,1,2,1,2,2,1,1,1}, Value::Integer(1)),
+            VarBinding::new(oid! {1,3,6,1,2,1,2,2,1,1,2}, Value::Integer(2)),
+            VarBinding::new(oid! {1,3,6,1,2,1,2,2,1,1,3}, Value::Integer(3)),
+        ]
+    }
+
+    #[test]
+    fn walk_many_with_repeated_root() {
+        let mut socket = agent(get_next_agent(system_mib()));
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        let groups = c.walk_many(&[oid! {1,3,6}, oid! {1,3,6}]).unwrap();
+        assert_eq!(groups[0].len(), 2);
+        assert_eq!(groups[1].len(), 2);
+    }
+
+    #[test]
+    fn walk_many_groups_by_root() {
+        for version in [Version::V1, Version::V2C].iter() {
+            let mut socket = agent(get_next_agent(two_subtree_mib()));
+            let mut c = Client::new(*version, &mut socket);
+
+            // The interface table outlasts the system group in the shared requests.
+            let roots = [oid! {1,3,6,1,2,1,2,2}, oid! {1,3,6,1,2,1,1}];
+            let groups = c.walk_many(&roots).unwrap();
+            assert_eq!(groups.len(), 2);
+
+            let names: Vec<_> = groups[0].iter().map(|vb| vb.name.clone()).collect();
+            assert_eq!(
+                names,
+                vec![
+                    oid! {1,3,6,1,2,1,2,2,1,1,1},
+                    oid! {1,3,6,1,2,1,2,2,1,1,2},
+                    oid! {1,3,6,1,2,1,2,2,1,1,3}
+                ]
+            );
+
+            let names: Vec<_> = groups[1].iter().map(|vb| vb.name.clone()).collect();
+            assert_eq!(
+                names,
+                vec![oid! {1,3,6,1,2,1,1,1,0}, oid! {1,3,6,1,2,1,1,5,0}]
+            );
+        }
+    }
+
+    #[test]
+    fn walk_many_outcomes_name_their_root() {
+        let mut socket = agent(get_next_agent(two_subtree_mib()));
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        // The interface table is walked on its own and within `interfaces`.
+        let roots = [oid! {1,3,6,1,2,1,2,2}, oid! {1,3,6,1,2,1,2}];
+        let outcomes = c.walk_many_limited(&roots, usize::MAX).unwrap();
+        let lens: Vec<_> = outcomes.iter().map(|o| o.bindings.len()).collect();
+        assert_eq!(lens, [3, 4]);
+        for (outcome, root) in outcomes.iter().zip(&roots) {
+            assert_eq!(&outcome.root, root);
+            assert!(outcome
+                .bindings
+                .iter()
+                .all(|vb| vb.name.strip_prefix(&outcome.root).is_some()));
+        }
+
+        let outcome = c.walk_limited(&roots[1], 2).unwrap();
+        assert_eq!(outcome.root, roots[1]);
+    }
+
+    #[test]
+    fn walk_range_stops_before_end() {
+        let mut socket = agent(get_next_agent(two_subtree_mib()));
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        let vars = c
+            .walk_range(&oid! {1,3,6,1,2,1,1,1,0}, &oid! {1,3,6,1,2,1,2,2,1,1,2})
+            .unwrap();
+        let names: Vec<_> = vars.iter().map(|vb| vb.name.clone()).collect();
+        assert_eq!(
+            names,
+            vec![
+                oid! {1,3,6,1,2,1,1,5,0},
+                oid! {1,3,6,1,2,1,2,1,0},
+                oid! {1,3,6,1,2,1,2,2,1,1,1}
+            ]
+        );
+
+        // Running off the end of the MIB ends the range as well.
+        let vars = c
+            .walk_range(&oid! {1,3,6,1,2,1,2,2,1,1,2}, &oid! {1,3,6,1,3})
+            .unwrap();
+        assert_eq!(vars.len(), 1);
+    }
+
+    /// Answers the first three GetNexts in order, then loops back to the first.
+    fn looping_agent() -> impl FnMut(&Message) -> Message {
+        let mut n = 0;
+        move |request| {
+            n = n % 3 + 1;
+            let bindings: Vec<_> = request
+                .data()
+                .bindings()
+                .iter()
+                .map(|_| VarBinding::new(oid! {1,3,6,1,4,1,1}.child(&[n]), Value::Integer(1)))
+                .collect();
+            response(request, &bindings)
+        }
+    }
+
+    #[test]
+    fn walk_reports_subtree_boundary() {
+        let mut socket = agent(get_bulk_agent(two_subtree_mib()));
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        let outcome = c
+            .bulk_walk_limited(&oid! {1,3,6,1,2,1,1}, 10, usize::MAX)
+            .unwrap();
+        assert_eq!(outcome.bindings.len(), 2);
+        assert_eq!(outcome.reason, StopReason::EndOfSubtree);
+        assert_eq!(outcome.boundary, Some(oid! {1,3,6,1,2,1,2,1,0}));
+
+        let mut socket = agent(get_next_agent(two_subtree_mib()));
+        let mut c = Client::new(Version::V2C, &mut socket);
+        let outcome = c.walk_limited(&oid! {1,3,6,1,2,1,2,1}, 10).unwrap();
+        assert_eq!(outcome.boundary, Some(oid! {1,3,6,1,2,1,2,2,1,1,1}));
+
+        let outcome = c.walk_limited(&oid! {1,3,6,1,2,1,2,2}, 10).unwrap();
+        assert_eq!(outcome.reason, StopReason::EndOfMibView);
+        assert_eq!(outcome.boundary, None);
+    }
+
+    #[test]
+    fn bulk_walk_many_drops_ended_columns() {
+        let entry = oid! {1,3,6,1,2,1,2,2,1};
+        // Column 2 has three rows, column 5 only one and then the MIB ends.
+        let mib: Vec<_> = [&[2, 1], &[2, 2], &[2, 3], &[5, 1]]
+            .iter()
+            .map(|arcs| VarBinding::new(entry.child(*arcs), Value::Integer(1)))
+            .collect();
+        let names = |bindings: &[VarBinding]| -> Vec<_> {
+            bindings.iter().map(|vb| vb.name.clone()).collect()
+        };
+        let requests = Rc::new(RefCell::new(vec![]));
+        let seen = requests.clone();
+        let mut respond = get_bulk_agent(mib);
+        let mut socket = MockTransport::agent(move |request| {
+            seen.borrow_mut().push(names(request.data().bindings()));
+            vec![respond(request)]
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        let columns = c
+            .bulk_walk_many(&[entry.child(&[2]), entry.child(&[5])], 2)
+            .unwrap();
+        assert_eq!(
+            names(&columns[0]),
+            [
+                entry.child(&[2, 1]),
+                entry.child(&[2, 2]),
+                entry.child(&[2, 3])
+            ]
+        );
+        assert_eq!(names(&columns[1]), [entry.child(&[5, 1])]);
+        // Column 5 ended with an `EndOfMIBView` in the first response, so only
+        // column 2 is continued.
+        assert_eq!(
+            *requests.borrow(),
+            [
+                vec![entry.child(&[2]), entry.child(&[5])],
+                vec![entry.child(&[2, 2])]
+            ]
+        );
+    }
+
+    /// Answers every GetBulk with `arcs` under 1.3.6.1.4.1.1, then an OID
+    /// past it.
+    fn fixed_bulk_agent(arcs: &'static [&'static [u32]]) -> impl FnMut(&Message) -> Message {
+        move |request| {
+            let mut bindings: Vec<_> = arcs
+                .iter()
+                .map(|arcs| VarBinding::new(oid! {1,3,6,1,4,1,1}.child(arcs), Value::Integer(1)))
+                .collect();
+            bindings.push(VarBinding::new(oid! {1,3,6,1,4,1,2}, Value::Integer(1)));
+            response(request, &bindings)
+        }
+    }
+
+    #[test]
+    fn lenient_walk_tolerates_non_increase_at_subtree_end() {
+        let root = oid! {1,3,6,1,4,1,1};
+        // Steps back to `.1.5` after `.2`, then leaves the subtree.
+        let mut socket = agent(fixed_bulk_agent(&[&[1], &[2], &[1, 5]]));
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        let r = c.bulk_walk(&root, 10);
+        assert!(matches!(r, Err(SnmpError::NonIncreasingOid(_))));
+
+        c.set_walk_strictness(WalkStrictness::Lenient);
+        let outcome = c.bulk_walk_limited(&root, 10, usize::MAX).unwrap();
+        assert_eq!(outcome.bindings.len(), 2);
+        assert_eq!(outcome.reason, StopReason::NonIncreasingOid);
+    }
+
+    #[test]
+    fn lenient_walk_fails_on_loop() {
+        let root = oid! {1,3,6,1,4,1,1};
+        // Keeps going inside the subtree after stepping back.
+        let mut socket = agent(fixed_bulk_agent(&[&[1], &[2], &[1, 5], &[1, 6]]));
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_walk_strictness(WalkStrictness::Lenient);
+
+        let r = c.bulk_walk(&root, 10);
+        assert!(matches!(r, Err(SnmpError::NonIncreasingOid(_))));
+    }
+
+    #[test]
+    fn lenient_bulk_walk_many_fails_on_looping_column() {
+        let root = oid! {1,3,6,1,4,1,1};
+        let mut socket = agent(fixed_bulk_agent(&[&[1], &[2], &[1, 5]]));
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_walk_strictness(WalkStrictness::Lenient);
+        let columns = c.bulk_walk_many(std::slice::from_ref(&root), 10).unwrap();
+        assert_eq!(columns[0].len(), 2);
+
+        let mut socket = agent(fixed_bulk_agent(&[&[1], &[2], &[1, 5], &[1, 6]]));
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_walk_strictness(WalkStrictness::Lenient);
+        let r = c.bulk_walk_many(std::slice::from_ref(&root), 10);
+        assert!(matches!(r, Err(SnmpError::NonIncreasingOid(_))));
+    }
+
+    /// Answers GetNexts under 1.3.6.1.4.1.1 with `.1` and `.2`, then steps
+    /// back to `.1.5`. After that it leaves the subtree or, when `looping`,
+    /// carries on inside it.
+    fn stepping_back_agent(looping: bool) -> impl FnMut(&Message) -> Message {
+        let root = oid! {1,3,6,1,4,1,1};
+        move |request| {
+            let bindings: Vec<_> = request
+                .data()
+                .bindings()
+                .iter()
+                .map(|vb| {
+                    let name = match vb.name.strip_prefix(&root).as_deref() {
+                        Some([]) => root.child(&[1]),
+                        Some([1]) => root.child(&[2]),
+                        Some([2]) => root.child(&[1, 5]),
+                        _ if looping => root.child(&[1, 6]),
+                        _ => oid! {1,3,6,1,4,1,2},
+                    };
+                    VarBinding::new(name, Value::Integer(1))
+                })
+                .collect();
+            response(request, &bindings)
+        }
+    }
+
+    #[test]
+    fn lenient_get_next_walk_tolerates_one_step_back() {
+        let root = oid! {1,3,6,1,4,1,1};
+        let end = oid! {1,3,6,1,4,1,2};
+        let mut socket = agent(stepping_back_agent(false));
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        assert!(matches!(c.walk(&root), Err(SnmpError::NonIncreasingOid(_))));
+        assert!(matches!(
+            c.walk_range(&root, &end),
+            Err(SnmpError::NonIncreasingOid(_))
+        ));
+
+        c.set_walk_strictness(WalkStrictness::Lenient);
+        let outcome = c.walk_limited(&root, 10).unwrap();
+        assert_eq!(outcome.bindings.len(), 2);
+        assert_eq!(outcome.reason, StopReason::NonIncreasingOid);
+        assert_eq!(c.walk_range(&root, &end).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn lenient_get_next_walk_fails_on_loop() {
+        let root = oid! {1,3,6,1,4,1,1};
+        let mut socket = agent(stepping_back_agent(true));
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_walk_strictness(WalkStrictness::Lenient);
+
+        let r = c.walk_limited(&root, 10);
+        assert!(matches!(r, Err(SnmpError::NonIncreasingOid(_))));
+        let r = c.walk_many(&[root.clone(), oid! {1,3,6,1,2}]);
+        assert!(matches!(r, Err(SnmpError::NonIncreasingOid(_))));
+        let r = c.walk_range(&root, &oid! {1,3,6,1,4,1,2});
+        assert!(matches!(r, Err(SnmpError::NonIncreasingOid(_))));
+    }
+
+    #[test]
+    fn walk_stops_at_duplicate_oid() {
+        let mut socket = agent(looping_agent());
+        let mut c = Client::new(Version::V2C, &mut socket);
+        let root = oid! {1,3,6,1,4,1,1};
+
+        let outcome = c.walk_limited(&root, 100).unwrap();
+        assert_eq!(outcome.bindings.len(), 3);
+        assert_eq!(outcome.reason, StopReason::DuplicateOid);
+
+        let mut socket = agent(looping_agent());
+        let mut c = Client::new(Version::V2C, &mut socket);
+        let outcome = c.bulk_walk_limited(&root, 1, 100).unwrap();
+        assert_eq!(outcome.bindings.len(), 3);
+        assert_eq!(outcome.reason, StopReason::DuplicateOid);
+    }
+
+    #[test]
+    fn walk_stops_at_subtree_end() {
+        let mut socket = agent(get_next_agent(system_mib()));
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        let vars = c.walk(&oid! {1,3,6,1,2,1,1,1}).unwrap();
+        assert_eq!(vars.len(), 1);
+    }
+
+    #[test]
+    fn bulk_walk_stops_at_subtree_end() {
+        let mut socket = agent(get_bulk_agent(two_subtree_mib()));
+        let mut c = Client::new(Version::V2C, &mut socket);
+
+        let outcome = c
+            .bulk_walk_limited(&oid! {1,3,6,1,2,1,1}, 2, usize::MAX)
+            .unwrap();
+        assert_eq!(outcome.bindings.len(), 2);
+        assert_eq!(outcome.reason, StopReason::EndOfSubtree);
+
+        let outcome = c
+            .bulk_walk_limited(&oid! {1,3,6,1,2,1,2,2}, 2, usize::MAX)
+            .unwrap();
+        assert_eq!(outcome.bindings.len(), 3);
+        assert_eq!(outcome.reason, StopReason::EndOfMibView);
+
+        let mut c = Client::new(Version::V1, &mut socket);
+        let r = c.bulk_walk(&oid! {1,3,6}, 2);
+        assert!(matches!(r, Err(SnmpError::Unsupported(_))));
+    }
+
+    /// An agent with an endless table under 1.3.6.1.4.1.1.
+    fn endless_agent(request: &Message) -> Message {
+        let next = |name: &ObjectIdentifier| {
+            let mut arcs = name.to_vec();
+            if arcs.len() <= 7 {
+                arcs = vec![1, 3, 6, 1, 4, 1, 1, 1];
+            } else {
+                *arcs.last_mut().unwrap() += 1;
+            }
+            ObjectIdentifier::new(arcs).unwrap()
+        };
+
+        let mut bindings = vec![];
+        for requested in request.data().bindings() {
+            let mut name = requested.name.clone();
+            let repetitions = match request.data().tag() {
+                PduTag::GetBulkRequest => request.data().bulk_repetitions().1,
+                _ => 1,
+            };
+            for _ in 0..repetitions {
+                name = next(&name);
+                bindings.push(VarBinding::new(name.clone(), Value::Integer(1)));
+            }
+        }
+
+        response(request, &bindings)
+    }
+
+    #[test]
+    fn walk_limited_stops_at_cap() {
+        let mut socket = agent(endless_agent);
+        let mut c = Client::new(Version::V2C, &mut socket);
+        let root = oid! {1,3,6,1,4,1,1};
+
+        let outcome = c.walk_limited(&root, 5).unwrap();
+        assert_eq!(outcome.bindings.len(), 5);
+        assert_eq!(outcome.reason, StopReason::LimitReached);
+        assert_eq!(outcome.bindings[4].name, oid! {1,3,6,1,4,1,1,5});
+
+        let outcome = c.bulk_walk_limited(&root, 3, 5).unwrap();
+        assert_eq!(outcome.bindings.len(), 5);
+        assert_eq!(outcome.reason, StopReason::LimitReached);
+        assert_eq!(outcome.bindings[4].name, oid! {1,3,6,1,4,1,1,5});
+    }
+
+    #[test]
+    fn walk_stops_when_budget_runs_out() {
+        // Every request is answered well within the timeout, but takes a second.
+        let clock = MockClock::new();
+        let agent_clock = clock.clone();
+        let mut socket = MockTransport::agent(move |request| {
+            agent_clock.advance(Duration::from_secs(1));
+            vec![endless_agent(request)]
+        });
+        let mut c = Client::new(Version::V2C, &mut socket);
+        c.set_clock(clock);
+        c.set_timeout(Some(Duration::from_secs(2)));
+        c.set_walk_budget(Some(Duration::from_millis(2500)));
+        let root = oid! {1,3,6,1,4,1,1};
+
+        let outcome = c.walk_limited(&root, 100).unwrap();
+        assert_eq!(outcome.bindings.len(), 3);
+        assert_eq!(outcome.reason, StopReason::BudgetExhausted);
+
+        let outcome = c.bulk_walk_limited(&root, 2, 100).unwrap();
+        assert_eq!(outcome.bindings.len(), 6);
+        assert_eq!(outcome.reason, StopReason::BudgetExhausted);
+
+        assert_eq!(c.walk(&root).unwrap().len(), 3);
+    }
+}